@@ -2,8 +2,10 @@ use anyhow::Result;
 use clap::Parser;
 use dotenvy::dotenv;
 use std::convert::Infallible;
+use std::path::PathBuf;
 use std::{env, fs, io};
-use twitter_tool_rs::twitter_client::TwitterClient;
+use twitter_tool_rs::twitter_client::mock_server::MockServer;
+use twitter_tool_rs::twitter_client::{TwitterClient, TwitterEndpoints};
 use twitter_tool_rs::ui;
 use twitter_tool_rs::user_config::UserConfig;
 
@@ -12,6 +14,12 @@ use twitter_tool_rs::user_config::UserConfig;
 struct Args {
     #[arg(short, long)]
     login: bool,
+    /// Local port for the OAuth redirect, i.e. `http://127.0.0.1:<PORT>`
+    #[arg(long, value_name = "PORT", default_value_t = 8080)]
+    callback_port: u16,
+    /// Serve the Twitter API from a local fixture directory instead of the network
+    #[arg(long, value_name = "DIR")]
+    mock_fixtures: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -20,15 +28,43 @@ async fn main() -> Result<()> {
 
     dotenv().ok();
 
-    let twitter_client_id = env::var("TWITTER_CLIENT_ID")?;
-    let twitter_client_secret = env::var("TWITTER_CLIENT_SECRET")?;
-    let mut twitter_client = TwitterClient::new(&twitter_client_id, &twitter_client_secret);
-    twitter_client.load_auth().or_else(|_| {
-        eprintln!("No auth file found, must login");
-        Ok::<_, Infallible>(())
-    })?;
-    twitter_client.authorize(!args.login).await?;
-    twitter_client.save_auth()?;
+    // NB: kept alive for the duration of [main]
+    let mut _mock_server = None;
+
+    let twitter_client = match &args.mock_fixtures {
+        Some(fixture_dir) => {
+            let mock_server = MockServer::start_with_fixture_dir(fixture_dir)?;
+            let endpoints = TwitterEndpoints::with_base_url(&mock_server.base_url())?;
+            let mut twitter_client = TwitterClient::new_with_endpoints("mock", "mock", endpoints);
+            twitter_client.set_access_token("mock");
+            _mock_server = Some(mock_server);
+            twitter_client
+        }
+        None => {
+            let twitter_client_id = env::var("TWITTER_CLIENT_ID")?;
+            let twitter_client_secret = env::var("TWITTER_CLIENT_SECRET")?;
+            let endpoints = match env::var("TWITTER_API_BASE_URL") {
+                Ok(base_url) => TwitterEndpoints::with_base_url(&base_url.parse()?)?,
+                Err(_) => TwitterEndpoints::default(),
+            };
+            let mut twitter_client = TwitterClient::new_with_endpoints(
+                &twitter_client_id,
+                &twitter_client_secret,
+                endpoints,
+            );
+            twitter_client.set_callback_port(args.callback_port);
+            if let Ok(bearer_token) = env::var("TWITTER_BEARER_TOKEN") {
+                twitter_client.set_app_access_token(&bearer_token);
+            }
+            twitter_client.load_auth().or_else(|_| {
+                eprintln!("No auth file found, must login");
+                Ok::<_, Infallible>(())
+            })?;
+            twitter_client.authorize(!args.login).await?;
+            twitter_client.save_auth()?;
+            twitter_client
+        }
+    };
 
     let me = twitter_client.me().await?;
     println!("{me:?}");
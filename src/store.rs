@@ -1,24 +1,122 @@
-use crate::twitter_client::{api, PagedResult, TwitterClient};
+use crate::twitter_client::{
+    api, PagedResult, TwitterApiError, TwitterClient, MAX_SEARCH_QUERY_LEN,
+};
 use crate::user_config::UserConfig;
 use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use futures_util::future::{join_all, try_join_all};
 use itertools::Itertools;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::future::Future;
 use std::sync::{Arc, Mutex};
-use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::{Mutex as AsyncMutex, Notify};
 
 // NB: all the synchronization and interior mutability are encapsulated here for granularity.
 // Also it seems slightly nicer as an API?  Esp. since methods don't have to be &mut self.
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engagement {
+    Like,
+    Retweet,
+    Bookmark,
+}
+
+/// Where the tweets in [Store::tweets_feed] came from, so that the next page can be loaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FeedSource {
+    #[default]
+    ReverseChronological,
+    User(String),
+    Search {
+        query: String,
+        window: api::SearchWindow,
+    },
+    Bookmarks,
+    List(String),
+    Mentions,
+    Stream,
+    /// Several searches merged into one feed, see [Store::load_merged_search_tweets].
+    MergedSearch(Vec<String>),
+}
+
+/// Whose accounts [Store::user_list] shows, so that the next page can be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserListSource {
+    Followers(String),
+    Following(String),
+}
+
+/// The combined pagination state of a [FeedSource::MergedSearch], kept (serialized) as its page
+/// token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct MergedSearchPage {
+    until_id: Option<String>,
+    // NB: indices of the searches that may have more results
+    queries: Vec<usize>,
+}
+
+/// The ids a [Store::load_tweets] call is fetching, released from [Store::tweets_in_flight] and
+/// their waiters notified however the call ends, including by being dropped.
+struct TweetsInFlight<'a> {
+    tweets_in_flight: &'a Mutex<HashMap<String, Arc<Notify>>>,
+    tweet_ids: Vec<String>,
+    fetched: Arc<Notify>,
+}
+
+impl Drop for TweetsInFlight<'_> {
+    fn drop(&mut self) {
+        let mut tweets_in_flight = self.tweets_in_flight.lock().unwrap();
+        for tweet_id in &self.tweet_ids {
+            tweets_in_flight.remove(tweet_id);
+        }
+        self.fetched.notify_waiters();
+    }
+}
+
+/// Bucketed tweet counts by author id and [api::Granularity].
+pub type TweetCounts = HashMap<(String, api::Granularity), Vec<api::TweetCount>>;
+
 // CR: move Arc up
 #[derive(Debug)]
 pub struct Store {
     pub twitter_client: TwitterClient,
     pub twitter_user: api::User,
     pub tweets: Arc<Mutex<HashMap<String, api::Tweet>>>,
+    // NB: every user seen this session, by id, so that authors are only looked up once
+    pub users: Arc<Mutex<HashMap<String, api::User>>>,
+    // NB: ids currently being fetched by [Store::load_tweets], so they're only requested once;
+    // other callers wait to be notified that the fetch is done
+    pub tweets_in_flight: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
     pub tweets_feed: Arc<Mutex<Vec<String>>>,
     pub tweets_feed_page_token: Arc<AsyncMutex<Option<String>>>,
+    pub tweets_feed_source: Arc<Mutex<FeedSource>>,
+    // NB: only what was liked/retweeted this session; the feeds don't say
+    pub liked_tweets: Arc<Mutex<HashSet<String>>>,
+    pub retweeted_tweets: Arc<Mutex<HashSet<String>>>,
+    pub bookmarked_tweets: Arc<Mutex<HashSet<String>>>,
+    pub lists: Arc<Mutex<Vec<api::List>>>,
+    // NB: ids into [Store::users]
+    pub user_list: Arc<Mutex<Vec<String>>>,
+    pub user_list_page_token: Arc<AsyncMutex<Option<String>>>,
+    pub user_list_source: Arc<Mutex<Option<UserListSource>>>,
+    // NB: `None` until [Store::load_followed_users], since toggling needs to know where it starts
+    pub followed_users: Arc<Mutex<Option<HashSet<String>>>>,
+    // NB: protected accounts we've asked to follow this session
+    pub pending_follows: Arc<Mutex<HashSet<String>>>,
+    // NB: muted or blocked on Twitter itself, as of [Store::load_muted_users]
+    pub muted_users: Arc<Mutex<HashSet<String>>>,
+    // NB: recent posting activity by author id, see [Store::load_tweet_counts]
+    pub tweet_counts: Arc<Mutex<TweetCounts>>,
+    // NB: authors whose counts failed, at any granularity, so as not to keep asking
+    pub tweet_counts_failed: Arc<Mutex<HashSet<String>>>,
+    // NB: most recently active first
+    pub dm_conversations: Arc<Mutex<Vec<api::DmConversation>>>,
+    // NB: by conversation id, oldest first
+    pub dm_events: Arc<Mutex<HashMap<String, Vec<api::DmEvent>>>>,
+    // NB: newest first, as pushed by [Store::follow_stream]
+    pub streamed_tweets: Arc<Mutex<Vec<String>>>,
     pub user_config: Arc<Mutex<UserConfig>>,
 }
 
@@ -32,8 +130,29 @@ impl Store {
             twitter_client,
             twitter_user: twitter_user.clone(),
             tweets: Arc::new(Mutex::new(HashMap::new())),
+            users: Arc::new(Mutex::new(HashMap::from([(
+                twitter_user.id.clone(),
+                twitter_user.clone(),
+            )]))),
+            tweets_in_flight: Arc::new(Mutex::new(HashMap::new())),
             tweets_feed: Arc::new(Mutex::new(Vec::new())),
             tweets_feed_page_token: Arc::new(AsyncMutex::new(None)),
+            tweets_feed_source: Arc::new(Mutex::new(FeedSource::default())),
+            liked_tweets: Arc::new(Mutex::new(HashSet::new())),
+            retweeted_tweets: Arc::new(Mutex::new(HashSet::new())),
+            bookmarked_tweets: Arc::new(Mutex::new(HashSet::new())),
+            lists: Arc::new(Mutex::new(Vec::new())),
+            user_list: Arc::new(Mutex::new(Vec::new())),
+            user_list_page_token: Arc::new(AsyncMutex::new(None)),
+            user_list_source: Arc::new(Mutex::new(None)),
+            followed_users: Arc::new(Mutex::new(None)),
+            pending_follows: Arc::new(Mutex::new(HashSet::new())),
+            muted_users: Arc::new(Mutex::new(HashSet::new())),
+            tweet_counts: Arc::new(Mutex::new(HashMap::new())),
+            tweet_counts_failed: Arc::new(Mutex::new(HashSet::new())),
+            dm_conversations: Arc::new(Mutex::new(Vec::new())),
+            dm_events: Arc::new(Mutex::new(HashMap::new())),
+            streamed_tweets: Arc::new(Mutex::new(Vec::new())),
             user_config: Arc::new(Mutex::new(user_config.clone())),
         }
     }
@@ -45,7 +164,522 @@ impl Store {
         Ok(())
     }
 
-    // pub async fn load_tweet(&self, tweet_id: &str) {}
+    fn engaged_tweets(&self, engagement: Engagement) -> &Mutex<HashSet<String>> {
+        match engagement {
+            Engagement::Like => &self.liked_tweets,
+            Engagement::Retweet => &self.retweeted_tweets,
+            Engagement::Bookmark => &self.bookmarked_tweets,
+        }
+    }
+
+    pub fn is_engaged(&self, engagement: Engagement, tweet_id: &str) -> bool {
+        self.engaged_tweets(engagement)
+            .lock()
+            .unwrap()
+            .contains(tweet_id)
+    }
+
+    fn set_engaged(&self, engagement: Engagement, tweet_id: &str, engaged: bool) {
+        let changed = {
+            let mut engaged_tweets = self.engaged_tweets(engagement).lock().unwrap();
+            if engaged {
+                engaged_tweets.insert(tweet_id.to_string())
+            } else {
+                engaged_tweets.remove(tweet_id)
+            }
+        };
+
+        if changed {
+            let mut tweets = self.tweets.lock().unwrap();
+            let public_metrics = tweets
+                .get_mut(tweet_id)
+                .and_then(|tweet| tweet.public_metrics.as_mut());
+            if let Some(public_metrics) = public_metrics {
+                let count = match engagement {
+                    Engagement::Like => Some(&mut public_metrics.like_count),
+                    Engagement::Retweet => Some(&mut public_metrics.retweet_count),
+                    Engagement::Bookmark => None,
+                };
+                if let Some(count) = count {
+                    *count += if engaged { 1 } else { -1 };
+                }
+            }
+        }
+    }
+
+    /// Optimistically flips [engagement] on [tweet_id], returning the new state; follow up with
+    /// [sync_engagement] to make it stick.
+    pub fn toggle_engagement(&self, engagement: Engagement, tweet_id: &str) -> bool {
+        let engaged = !self.is_engaged(engagement, tweet_id);
+        self.set_engaged(engagement, tweet_id, engaged);
+        engaged
+    }
+
+    /// Sends the result of [toggle_engagement] to the API, reverting it if that fails.
+    pub async fn sync_engagement(
+        &self,
+        engagement: Engagement,
+        tweet_id: &str,
+        engaged: bool,
+    ) -> Result<()> {
+        let user_id = &self.twitter_user.id;
+        let result = match (engagement, engaged) {
+            (Engagement::Like, true) => self.twitter_client.like_tweet(user_id, tweet_id).await,
+            (Engagement::Like, false) => self.twitter_client.unlike_tweet(user_id, tweet_id).await,
+            (Engagement::Retweet, true) => self.twitter_client.retweet(user_id, tweet_id).await,
+            (Engagement::Retweet, false) => self.twitter_client.unretweet(user_id, tweet_id).await,
+            (Engagement::Bookmark, true) => {
+                self.twitter_client.bookmark_tweet(user_id, tweet_id).await
+            }
+            (Engagement::Bookmark, false) => {
+                self.twitter_client
+                    .unbookmark_tweet(user_id, tweet_id)
+                    .await
+            }
+        };
+        if result.is_err() {
+            self.set_engaged(engagement, tweet_id, !engaged);
+        }
+        result
+    }
+
+    pub fn has_loaded_followed_users(&self) -> bool {
+        self.followed_users.lock().unwrap().is_some()
+    }
+
+    pub fn is_following(&self, user_id: &str) -> bool {
+        self.followed_users
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|followed_users| followed_users.contains(user_id))
+    }
+
+    pub fn is_pending_follow(&self, user_id: &str) -> bool {
+        self.pending_follows.lock().unwrap().contains(user_id)
+    }
+
+    fn set_following(&self, user_id: &str, following: bool) {
+        if let Some(followed_users) = self.followed_users.lock().unwrap().as_mut() {
+            if following {
+                followed_users.insert(user_id.to_string());
+            } else {
+                followed_users.remove(user_id);
+            }
+        }
+    }
+
+    /// Loads every account we follow into [Store::followed_users], across all pages.
+    pub async fn load_followed_users(&self) -> Result<()> {
+        let user_id = &self.twitter_user.id;
+        let followed_users =
+            load_all_pages(|page_token| self.twitter_client.following(user_id, page_token)).await?;
+        *self.followed_users.lock().unwrap() =
+            Some(followed_users.into_iter().map(|user| user.id).collect());
+        Ok(())
+    }
+
+    /// Like [toggle_engagement], but following [user_id]; follow up with [sync_following].
+    pub fn toggle_following(&self, user_id: &str) -> Result<bool> {
+        if !self.has_loaded_followed_users() {
+            return Err(anyhow!("Still loading who you follow"));
+        }
+        let following = !self.is_following(user_id);
+        self.set_following(user_id, following);
+        Ok(following)
+    }
+
+    /// Sends the result of [toggle_following] to the API, reverting it if that fails; a
+    /// protected account only becomes pending, see [Store::pending_follows].
+    pub async fn sync_following(&self, user_id: &str, following: bool) -> Result<()> {
+        let source_user_id = &self.twitter_user.id;
+        let result = if following {
+            self.twitter_client
+                .follow_user(source_user_id, user_id)
+                .await
+                .map(|follow_status| {
+                    if !follow_status.following {
+                        self.set_following(user_id, false);
+                    }
+                    if follow_status.pending_follow == Some(true) {
+                        self.pending_follows
+                            .lock()
+                            .unwrap()
+                            .insert(user_id.to_string());
+                    }
+                })
+        } else {
+            self.twitter_client
+                .unfollow_user(source_user_id, user_id)
+                .await
+        };
+        if result.is_err() {
+            self.set_following(user_id, !following);
+        }
+        result
+    }
+
+    /// Whether [tweet] should be hidden from feeds, see [is_muted_by].
+    pub fn is_muted(&self, tweet: &api::Tweet) -> bool {
+        let user_config = self.user_config.lock().unwrap();
+        let muted_users = self.muted_users.lock().unwrap();
+        is_muted_by(&user_config, &muted_users, tweet)
+    }
+
+    /// Like [toggle_following], but muting [user_id] on Twitter; follow up with
+    /// [sync_muted_user].
+    pub fn toggle_muted_user(&self, user_id: &str) -> bool {
+        let mut muted_users = self.muted_users.lock().unwrap();
+        let muted = !muted_users.remove(user_id);
+        if muted {
+            muted_users.insert(user_id.to_string());
+        }
+        muted
+    }
+
+    /// Sends the result of [toggle_muted_user] to the API, reverting it if that fails.
+    pub async fn sync_muted_user(&self, user_id: &str, muted: bool) -> Result<()> {
+        let source_user_id = &self.twitter_user.id;
+        let result = if muted {
+            self.twitter_client.mute_user(source_user_id, user_id).await
+        } else {
+            self.twitter_client
+                .unmute_user(source_user_id, user_id)
+                .await
+        };
+        if result.is_err() {
+            self.toggle_muted_user(user_id);
+        }
+        result
+    }
+
+    /// Loads every account we've muted or blocked on Twitter into [Store::muted_users].
+    ///
+    /// NB: one of the two failing keeps what the other found, and a 403, i.e. a token from before
+    /// the `mute.read`/`block.read` scopes, counts as nobody, since local mutes work regardless.
+    pub async fn load_muted_users(&self) -> Result<()> {
+        let user_id = &self.twitter_user.id;
+        let (muting, blocking) = tokio::join!(
+            load_all_pages(|page_token| self.twitter_client.muting(user_id, page_token)),
+            load_all_pages(|page_token| self.twitter_client.blocking(user_id, page_token)),
+        );
+
+        let mut muted_users = HashSet::new();
+        let mut first_error = None;
+        for result in [muting, blocking] {
+            match result {
+                Ok(users) => muted_users.extend(users.into_iter().map(|user| user.id)),
+                Err(error)
+                    if error
+                        .downcast_ref::<TwitterApiError>()
+                        .is_some_and(|api_error| api_error.is_forbidden()) => {}
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        *self.muted_users.lock().unwrap() = muted_users;
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Counts [user_id]'s tweets over the last week, once per session and [granularity].
+    ///
+    /// NB: a no-op without an app token, which the endpoint requires, and a failure is only
+    /// reported the once, see [Store::tweet_counts_failed].
+    pub async fn load_tweet_counts(
+        &self,
+        user_id: &str,
+        granularity: api::Granularity,
+    ) -> Result<()> {
+        let key = (user_id.to_string(), granularity);
+        if !self.twitter_client.has_app_access_token()
+            || self.tweet_counts.lock().unwrap().contains_key(&key)
+            || self.tweet_counts_failed.lock().unwrap().contains(user_id)
+        {
+            return Ok(());
+        }
+
+        let query = format!("from:{user_id}");
+        match self.twitter_client.tweet_counts(&query, granularity).await {
+            Ok(tweet_counts) => {
+                self.tweet_counts.lock().unwrap().insert(key, tweet_counts);
+                Ok(())
+            }
+            Err(error) => {
+                self.tweet_counts_failed
+                    .lock()
+                    .unwrap()
+                    .insert(user_id.to_string());
+                Err(error)
+            }
+        }
+    }
+
+    /// Loads a page of followers or following into [Store::user_list], the way
+    /// [Store::load_tweets_feed] does for tweets.
+    pub async fn load_user_list(&self, source: &UserListSource, restart: bool) -> Result<()> {
+        let mut user_list_page_token = self
+            .user_list_page_token
+            .try_lock()
+            .with_context(|| anyhow!("Already in-flight"))?;
+
+        let mut maybe_page_token = None;
+        if !restart {
+            if self.user_list_source.lock().unwrap().as_ref() != Some(source) {
+                return Err(anyhow!("User list has changed, restart it instead"));
+            }
+            let next_page_token = user_list_page_token
+                .as_ref()
+                .ok_or(anyhow!("No more pages"))?;
+            maybe_page_token = Some(next_page_token.clone());
+        }
+
+        let (new_users, page_token) = match source {
+            UserListSource::Followers(user_id) => {
+                self.twitter_client
+                    .followers(user_id, maybe_page_token)
+                    .await?
+            }
+            UserListSource::Following(user_id) => {
+                self.twitter_client
+                    .following(user_id, maybe_page_token)
+                    .await?
+            }
+        };
+        *user_list_page_token = page_token;
+        *self.user_list_source.lock().unwrap() = Some(source.clone());
+
+        let mut new_user_ids = new_users
+            .iter()
+            .map(|user| user.id.clone())
+            .collect::<Vec<String>>();
+        if *source == UserListSource::Following(self.twitter_user.id.clone()) {
+            if let Some(followed_users) = self.followed_users.lock().unwrap().as_mut() {
+                followed_users.extend(new_user_ids.iter().cloned());
+            }
+        }
+        {
+            let mut users = self.users.lock().unwrap();
+            for user in new_users {
+                users.insert(user.id.clone(), user);
+            }
+        }
+        let mut user_list = self.user_list.lock().unwrap();
+        if restart {
+            *user_list = new_user_ids;
+        } else {
+            user_list.append(&mut new_user_ids);
+        }
+        Ok(())
+    }
+
+    pub async fn load_tweet(&self, tweet_id: &str) -> Result<()> {
+        self.load_tweets(&[tweet_id.to_string()]).await
+    }
+
+    /// Fetches whichever of [tweet_ids] aren't in [Store::tweets] yet.  Ids that a concurrent
+    /// call is already fetching aren't requested twice; this waits for that call instead, and
+    /// if its fetch fails, those ids are left missing rather than reported here.
+    pub async fn load_tweets(&self, tweet_ids: &[String]) -> Result<()> {
+        let fetched = Arc::new(Notify::new());
+        let mut missing_ids = Vec::new();
+        let mut other_fetches: Vec<Arc<Notify>> = Vec::new();
+        let waiting_for_others;
+        {
+            let tweets = self.tweets.lock().unwrap();
+            let mut tweets_in_flight = self.tweets_in_flight.lock().unwrap();
+            for tweet_id in tweet_ids.iter().unique() {
+                if tweets.contains_key(tweet_id) {
+                    continue;
+                }
+                match tweets_in_flight.get(tweet_id) {
+                    Some(other_fetch) => other_fetches.push(other_fetch.clone()),
+                    None => {
+                        tweets_in_flight.insert(tweet_id.clone(), fetched.clone());
+                        missing_ids.push(tweet_id.clone());
+                    }
+                }
+            }
+            // NB: registered while still locked, so that a fetch finishing right after can't be
+            // missed
+            waiting_for_others = join_all(
+                other_fetches
+                    .iter()
+                    .unique_by(|other_fetch| Arc::as_ptr(other_fetch))
+                    .map(|other_fetch| other_fetch.notified()),
+            );
+        }
+
+        let in_flight = TweetsInFlight {
+            tweets_in_flight: &self.tweets_in_flight,
+            tweet_ids: missing_ids.clone(),
+            fetched,
+        };
+        let mut result = Ok(());
+        if !missing_ids.is_empty() {
+            result = self.fetch_tweets(&missing_ids).await;
+        }
+        drop(in_flight);
+
+        // NB: if another caller's fetch failed, it reports the error; these ids are just missing
+        waiting_for_others.await;
+        result?;
+        self.load_missing_authors(&missing_ids).await;
+        Ok(())
+    }
+
+    async fn fetch_tweets(&self, tweet_ids: &[String]) -> Result<()> {
+        let new_tweets = self.twitter_client.tweets_by_ids(tweet_ids).await?;
+        let mut tweets = self.tweets.lock().unwrap();
+        let mut users = self.users.lock().unwrap();
+        for tweet in new_tweets {
+            insert_tweet(&mut tweets, &mut users, tweet);
+        }
+        Ok(())
+    }
+
+    /// Looks up the authors of [tweet_ids] that neither came with them nor are in
+    /// [Store::users] yet.  Best-effort: the tweets are already loaded, so a failed lookup is
+    /// only kept as a warning and they're shown without their authors.
+    pub async fn load_missing_authors(&self, tweet_ids: &[String]) {
+        let missing_ids: Vec<String> = {
+            let tweets = self.tweets.lock().unwrap();
+            tweet_ids
+                .iter()
+                .filter_map(|tweet_id| tweets.get(tweet_id))
+                .filter(|tweet| tweet.author.is_none())
+                .map(|tweet| tweet.author_id.clone())
+                .unique()
+                .collect()
+        };
+        if missing_ids.is_empty() {
+            return;
+        }
+
+        let authors = match self.twitter_client.users_by_ids(&missing_ids).await {
+            Ok(authors) => authors,
+            Err(error) => {
+                let warning = api::Error::new("Author lookup failed", &error.to_string());
+                self.twitter_client.add_warning(warning);
+                return;
+            }
+        };
+        let mut tweets = self.tweets.lock().unwrap();
+        let mut users = self.users.lock().unwrap();
+        for author in authors {
+            users.insert(author.id.clone(), author);
+        }
+        for tweet_id in tweet_ids {
+            if let Some(tweet) = tweets.get_mut(tweet_id) {
+                if let (None, Some(author)) = (&tweet.author, users.get(&tweet.author_id)) {
+                    tweet.author = Some(author.clone());
+                }
+            }
+        }
+    }
+
+    /// [TwitterClient::user_by_username], answered from [Store::users] if possible.
+    pub async fn user_by_username(&self, username: &str) -> Result<api::User> {
+        let cached = self
+            .users
+            .lock()
+            .unwrap()
+            .values()
+            .find(|user| user.username.eq_ignore_ascii_case(username))
+            .cloned();
+        if let Some(user) = cached {
+            return Ok(user);
+        }
+
+        let user = self.twitter_client.user_by_username(username).await?;
+        self.users
+            .lock()
+            .unwrap()
+            .insert(user.id.clone(), user.clone());
+        Ok(user)
+    }
+
+    /// Hydrates whatever [tweet_ids] reply to, quote or retweet.
+    pub async fn load_referenced_tweets(&self, tweet_ids: &[String]) -> Result<()> {
+        let referenced_ids: Vec<String> = {
+            let tweets = self.tweets.lock().unwrap();
+            tweet_ids
+                .iter()
+                .filter_map(|tweet_id| tweets.get(tweet_id))
+                .flat_map(|tweet| tweet.referenced_tweets.iter().flatten())
+                .map(|reference| reference.id.clone())
+                .unique()
+                .collect()
+        };
+        self.load_tweets(&referenced_ids).await
+    }
+
+    /// Walks up the `replied_to` chain from [tweet_id] to its conversation root, loading tweets
+    /// as needed.  Returns the ancestors root first; the walk stops early at a tweet that can't be
+    /// loaded (e.g. deleted), which is still included.
+    pub async fn load_thread(&self, tweet_id: &str) -> Result<Vec<String>> {
+        fn parent_of(tweets: &HashMap<String, api::Tweet>, tweet_id: &str) -> Option<String> {
+            let tweet = tweets.get(tweet_id)?;
+            let reference = tweet
+                .referenced_tweets
+                .iter()
+                .flatten()
+                .find(|reference| reference.r#type == "replied_to")?;
+            Some(reference.id.clone())
+        }
+
+        let mut ancestor_ids = Vec::new();
+        let mut current_id = tweet_id.to_string();
+        loop {
+            let Some(parent_id) = parent_of(&self.tweets.lock().unwrap(), &current_id) else {
+                break;
+            };
+            // NB: guard against cycles, which shouldn't happen but would hang the pane
+            if ancestor_ids.contains(&parent_id) || parent_id == tweet_id {
+                break;
+            }
+            self.load_tweet(&parent_id).await?;
+            ancestor_ids.push(parent_id.clone());
+            current_id = parent_id;
+        }
+
+        ancestor_ids.reverse();
+        Ok(ancestor_ids)
+    }
+
+    /// Loads one page of direct replies to [tweet_id], oldest first, by searching its
+    /// conversation.  NB: recent search only covers the last week.
+    pub async fn load_replies(&self, tweet_id: &str) -> Result<Vec<String>> {
+        let conversation_id = self
+            .tweets
+            .lock()
+            .unwrap()
+            .get(tweet_id)
+            .and_then(|tweet| tweet.conversation_id.clone())
+            .unwrap_or(tweet_id.to_string());
+        let query = format!("conversation_id:{conversation_id}");
+        let (conversation, _) = self
+            .twitter_client
+            .search_tweets(&query, &api::SearchWindow::default(), None)
+            .await?;
+
+        let mut reply_ids = Vec::new();
+        let mut tweets = self.tweets.lock().unwrap();
+        let mut users = self.users.lock().unwrap();
+        for tweet in conversation {
+            let is_reply = tweet
+                .referenced_tweets
+                .iter()
+                .flatten()
+                .any(|reference| reference.r#type == "replied_to" && reference.id == tweet_id);
+            if is_reply {
+                reply_ids.push(tweet.id.clone());
+            }
+            insert_tweet(&mut tweets, &mut users, tweet);
+        }
+        reply_ids.reverse();
+        Ok(reply_ids)
+    }
 
     // CR: need to sift results
     // CR: need a fixed page size, then call the twitter_client as many times as needed to achieve
@@ -55,6 +689,7 @@ impl Store {
         G: Fn(Option<String>) -> F,
     >(
         &self,
+        source: FeedSource,
         g: G,
         restart: bool,
     ) -> Result<()> {
@@ -66,22 +701,30 @@ impl Store {
         let mut maybe_page_token = None;
         // NB: require page token if continuing to next page
         if !restart {
+            if *self.tweets_feed_source.lock().unwrap() != source {
+                return Err(anyhow!("Feed has changed, restart it instead"));
+            }
             let next_page_token = tweets_page_token.as_ref().ok_or(anyhow!("No more pages"))?;
             maybe_page_token = Some(next_page_token.clone());
+        } else {
+            self.twitter_client.clear_warnings();
         }
 
         let (new_tweets, page_token) = g(maybe_page_token).await?;
         let mut new_tweets_reverse_chronological: Vec<String> = Vec::new();
 
         *tweets_page_token = page_token;
+        *self.tweets_feed_source.lock().unwrap() = source;
 
         {
             let mut tweets = self.tweets.lock().unwrap();
+            let mut users = self.users.lock().unwrap();
             for tweet in new_tweets {
                 new_tweets_reverse_chronological.push(tweet.id.clone());
-                tweets.insert(tweet.id.clone(), tweet);
+                insert_tweet(&mut tweets, &mut users, tweet);
             }
         }
+        let new_tweet_ids = new_tweets_reverse_chronological.clone();
         {
             let mut tweets_reverse_chronological = self.tweets_feed.lock().unwrap();
             if restart {
@@ -91,11 +734,202 @@ impl Store {
             }
         }
 
+        self.load_missing_authors(&new_tweet_ids).await;
+        Ok(())
+    }
+
+    /// Loads the most recent DMs of every conversation into [Store::dm_conversations] and
+    /// [Store::dm_events].
+    pub async fn load_dm_events(&self) -> Result<()> {
+        let (dm_events, _) = self.twitter_client.dm_events(None).await?;
+        self.insert_dm_events(dm_events);
+        Ok(())
+    }
+
+    /// Loads the most recent DMs of [dm_conversation_id], which may go further back than
+    /// [Store::load_dm_events] did.
+    pub async fn load_dm_conversation(&self, dm_conversation_id: &str) -> Result<()> {
+        let (dm_events, _) = self
+            .twitter_client
+            .dm_conversation_events(dm_conversation_id, None)
+            .await?;
+        self.insert_dm_events(dm_events);
         Ok(())
     }
 
+    /// Sends [text] to [dm_conversation_id] and, like [Store::create_tweet], fills in the new
+    /// event locally so that it can be shown immediately.
+    pub async fn send_dm(&self, dm_conversation_id: &str, text: &str) -> Result<()> {
+        let created = self
+            .twitter_client
+            .send_dm(dm_conversation_id, text)
+            .await?;
+        self.insert_dm_events(vec![api::DmEvent {
+            id: created.dm_event_id,
+            event_type: "MessageCreate".to_string(),
+            text: Some(text.to_string()),
+            sender_id: Some(self.twitter_user.id.clone()),
+            dm_conversation_id: Some(created.dm_conversation_id),
+            created_at: Some(Local::now()),
+            participant_ids: None,
+            sender: Some(self.twitter_user.clone()),
+            participants: Vec::new(),
+        }]);
+        Ok(())
+    }
+
+    fn insert_dm_events(&self, new_dm_events: Vec<api::DmEvent>) {
+        let mut dm_conversations = self.dm_conversations.lock().unwrap();
+        let mut dm_events = self.dm_events.lock().unwrap();
+        let mut users = self.users.lock().unwrap();
+
+        for dm_event in new_dm_events {
+            let Some(dm_conversation_id) = dm_event.dm_conversation_id.clone() else {
+                continue;
+            };
+            for user in dm_event.sender.iter().chain(&dm_event.participants) {
+                users.insert(user.id.clone(), user.clone());
+            }
+
+            let index = match dm_conversations
+                .iter()
+                .position(|dm_conversation| dm_conversation.id == dm_conversation_id)
+            {
+                Some(index) => index,
+                None => {
+                    dm_conversations.push(api::DmConversation {
+                        id: dm_conversation_id.clone(),
+                        // NB: one-to-one conversations are named `{id}-{id}` after both
+                        // participants, group conversations get an id of their own
+                        participant_ids: match dm_conversation_id.split_once('-') {
+                            Some((a, b)) => vec![a.to_string(), b.to_string()],
+                            None => Vec::new(),
+                        },
+                        updated_at: None,
+                    });
+                    dm_conversations.len() - 1
+                }
+            };
+            let dm_conversation = &mut dm_conversations[index];
+            let participant_ids = dm_event
+                .participant_ids
+                .iter()
+                .flatten()
+                .chain(dm_event.sender_id.iter());
+            for participant_id in participant_ids {
+                if !dm_conversation.participant_ids.contains(participant_id) {
+                    dm_conversation.participant_ids.push(participant_id.clone());
+                }
+            }
+            dm_conversation.participant_ids.sort_by_key(|id| id_key(id));
+            if dm_event.created_at > dm_conversation.updated_at {
+                dm_conversation.updated_at = dm_event.created_at;
+            }
+
+            let conversation_events = dm_events.entry(dm_conversation_id).or_default();
+            if !conversation_events
+                .iter()
+                .any(|existing| existing.id == dm_event.id)
+            {
+                conversation_events.push(dm_event);
+            }
+        }
+
+        for conversation_events in dm_events.values_mut() {
+            conversation_events.sort_by_key(|dm_event| id_key(&dm_event.id));
+        }
+        dm_conversations
+            .sort_by_key(|dm_conversation| std::cmp::Reverse(dm_conversation.updated_at));
+    }
+
+    /// Posts [new_tweet] and, since the API only echoes back its id and text, fills in the rest
+    /// of the tweet locally so that it can be shown immediately, at the top of the feed if it's
+    /// one of our own tweets would appear in.
+    pub async fn create_tweet(&self, new_tweet: &api::NewTweet) -> Result<String> {
+        let created = self.twitter_client.create_tweet(new_tweet).await?;
+
+        let mut referenced_tweets = Vec::new();
+        if let Some(reply) = &new_tweet.reply {
+            referenced_tweets.push(api::TweetReference {
+                r#type: "replied_to".to_string(),
+                id: reply.in_reply_to_tweet_id.clone(),
+            });
+        }
+        if let Some(quote_tweet_id) = &new_tweet.quote_tweet_id {
+            referenced_tweets.push(api::TweetReference {
+                r#type: "quoted".to_string(),
+                id: quote_tweet_id.clone(),
+            });
+        }
+
+        let mut tweets = self.tweets.lock().unwrap();
+        let conversation_id = match &new_tweet.reply {
+            Some(reply) => tweets
+                .get(&reply.in_reply_to_tweet_id)
+                .and_then(|tweet| tweet.conversation_id.clone()),
+            None => Some(created.id.clone()),
+        };
+        let tweet = api::Tweet {
+            id: created.id.clone(),
+            text: created.text,
+            created_at: Local::now(),
+            author_id: self.twitter_user.id.clone(),
+            author: Some(self.twitter_user.clone()),
+            conversation_id,
+            referenced_tweets: (!referenced_tweets.is_empty()).then_some(referenced_tweets),
+            attachments: None,
+            public_metrics: None,
+            entities: None,
+            note_tweet: None,
+            geo: None,
+            media: None,
+            poll: None,
+            place: None,
+            included_tweets: None,
+        };
+        tweets.insert(created.id.clone(), tweet);
+        let in_feed = match &*self.tweets_feed_source.lock().unwrap() {
+            FeedSource::ReverseChronological => true,
+            FeedSource::User(user_id) => *user_id == self.twitter_user.id,
+            _ => false,
+        };
+        if in_feed {
+            self.tweets_feed
+                .lock()
+                .unwrap()
+                .insert(0, created.id.clone());
+        }
+
+        Ok(created.id)
+    }
+
+    pub async fn load_feed(&self, source: &FeedSource, restart: bool) -> Result<()> {
+        match source {
+            FeedSource::ReverseChronological => {
+                self.load_tweets_reverse_chronological(restart).await
+            }
+            FeedSource::User(user_id) => self.load_user_tweets(user_id, restart).await,
+            FeedSource::Search { query, window } => {
+                self.load_search_tweets(query, window, restart).await
+            }
+            FeedSource::Bookmarks => self.load_bookmarks(restart).await,
+            FeedSource::List(list_id) => self.load_list_tweets(list_id, restart).await,
+            FeedSource::Mentions => self.load_mentions(restart).await,
+            FeedSource::Stream => self.load_streamed_tweets(restart).await,
+            FeedSource::MergedSearch(queries) => {
+                self.load_merged_search_tweets(queries, restart).await
+            }
+        }
+    }
+
+    pub async fn load_tweets_feed_next_page(&self) -> Result<()> {
+        let source = self.tweets_feed_source.lock().unwrap().clone();
+        self.load_feed(&source, false).await
+    }
+
     pub async fn load_tweets_reverse_chronological(&self, restart: bool) -> Result<()> {
         self.load_tweets_feed(
+            FeedSource::ReverseChronological,
             move |maybe_page_token| async move {
                 self.twitter_client
                     .timeline_reverse_chronological(&self.twitter_user.id, maybe_page_token)
@@ -108,6 +942,7 @@ impl Store {
 
     pub async fn load_user_tweets(&self, user_id: &str, restart: bool) -> Result<()> {
         self.load_tweets_feed(
+            FeedSource::User(user_id.to_string()),
             move |maybe_page_token| async move {
                 self.twitter_client
                     .user_tweets(user_id, maybe_page_token)
@@ -118,14 +953,1182 @@ impl Store {
         .await
     }
 
-    pub async fn load_search_tweets(&self, query: &str, restart: bool) -> Result<()> {
+    pub async fn load_search_tweets(
+        &self,
+        query: &str,
+        window: &api::SearchWindow,
+        restart: bool,
+    ) -> Result<()> {
         self.load_tweets_feed(
-            move |_maybe_page_token| {
-                let query = query.clone();
-                async move { self.twitter_client.search_tweets(&query).await }
+            FeedSource::Search {
+                query: query.to_string(),
+                window: window.clone(),
+            },
+            move |maybe_page_token| async move {
+                self.twitter_client
+                    .search_tweets(query, window, maybe_page_token)
+                    .await
+            },
+            restart,
+        )
+        .await
+    }
+
+    /// Searches for tweets from any starred account, splitting the query as needed to stay
+    /// under [MAX_SEARCH_QUERY_LEN].
+    pub async fn load_starred_accounts_tweets(&self, restart: bool) -> Result<()> {
+        let terms = self
+            .user_config
+            .lock()
+            .unwrap()
+            .starred_accounts
+            .keys()
+            .map(|user_id| format!("from:{user_id}"))
+            .sorted()
+            .collect::<Vec<String>>();
+        if terms.is_empty() {
+            return Err(anyhow!("No starred accounts"));
+        }
+        let queries = split_or_query(&terms, MAX_SEARCH_QUERY_LEN);
+        self.load_merged_search_tweets(&queries, restart).await
+    }
+
+    /// Runs [queries] concurrently (the rate limiter paces them) and merges their results into
+    /// one reverse-chronological feed.
+    ///
+    /// NB: a page only reaches back as far as every search that has more results got to, since
+    /// beyond that one of them may be missing tweets; the next page resumes from there using
+    /// `until_id` rather than each search's own `next_token`.
+    pub async fn load_merged_search_tweets(&self, queries: &[String], restart: bool) -> Result<()> {
+        self.load_tweets_feed(
+            FeedSource::MergedSearch(queries.to_vec()),
+            move |maybe_page_token| async move {
+                let page = match maybe_page_token {
+                    Some(page_token) => serde_json::from_str(&page_token)?,
+                    None => MergedSearchPage {
+                        until_id: None,
+                        queries: (0..queries.len()).collect(),
+                    },
+                };
+                let window = api::SearchWindow {
+                    until_id: page.until_id.clone(),
+                    ..api::SearchWindow::default()
+                };
+                let results = try_join_all(page.queries.iter().map(|&index| {
+                    self.twitter_client
+                        .search_tweets(&queries[index], &window, None)
+                }))
+                .await?;
+
+                let (tweets, next_page) = merge_search_results(&page.queries, results);
+                let page_token = next_page
+                    .map(|next_page| serde_json::to_string(&next_page))
+                    .transpose()?;
+                Ok((tweets, page_token))
+            },
+            restart,
+        )
+        .await
+    }
+
+    pub async fn load_mentions(&self, restart: bool) -> Result<()> {
+        self.load_tweets_feed(
+            FeedSource::Mentions,
+            move |maybe_page_token| async move {
+                self.twitter_client
+                    .user_mentions(&self.twitter_user.id, maybe_page_token)
+                    .await
+            },
+            restart,
+        )
+        .await
+    }
+
+    pub async fn load_bookmarks(&self, restart: bool) -> Result<()> {
+        self.load_tweets_feed(
+            FeedSource::Bookmarks,
+            move |maybe_page_token| async move {
+                let (tweets, page_token) = self
+                    .twitter_client
+                    .bookmarks(&self.twitter_user.id, maybe_page_token)
+                    .await?;
+                let mut bookmarked_tweets = self.bookmarked_tweets.lock().unwrap();
+                bookmarked_tweets.extend(tweets.iter().map(|tweet| tweet.id.clone()));
+                Ok((tweets, page_token))
+            },
+            restart,
+        )
+        .await
+    }
+
+    /// Loads the lists the user owns, followed by the ones they follow, into [Store::lists].
+    pub async fn load_lists(&self) -> Result<()> {
+        let user_id = &self.twitter_user.id;
+        let (owned_lists, followed_lists) = tokio::try_join!(
+            load_all_pages(|page_token| self.twitter_client.owned_lists(user_id, page_token)),
+            load_all_pages(|page_token| self.twitter_client.followed_lists(user_id, page_token)),
+        )?;
+        let lists = owned_lists
+            .into_iter()
+            .chain(followed_lists)
+            .unique_by(|list| list.id.clone())
+            .collect();
+        *self.lists.lock().unwrap() = lists;
+        Ok(())
+    }
+
+    pub async fn load_list_tweets(&self, list_id: &str, restart: bool) -> Result<()> {
+        self.load_tweets_feed(
+            FeedSource::List(list_id.to_string()),
+            move |maybe_page_token| async move {
+                self.twitter_client
+                    .list_tweets(list_id, maybe_page_token)
+                    .await
+            },
+            restart,
+        )
+        .await
+    }
+
+    /// Follows the filtered stream into [Store::streamed_tweets], calling [on_update] as tweets
+    /// arrive.  While the stream is the feed, they're also put at the top of [Store::tweets_feed].
+    pub async fn follow_stream(&self, on_update: impl Fn()) -> Result<()> {
+        self.twitter_client
+            .filtered_stream(|tweet| {
+                let tweet_id = tweet.id.clone();
+                {
+                    let mut streamed_tweets = self.streamed_tweets.lock().unwrap();
+                    // NB: a reconnect may replay tweets
+                    if streamed_tweets.contains(&tweet_id) {
+                        return;
+                    }
+                    streamed_tweets.insert(0, tweet_id.clone());
+                }
+                insert_tweet(
+                    &mut self.tweets.lock().unwrap(),
+                    &mut self.users.lock().unwrap(),
+                    tweet,
+                );
+                if *self.tweets_feed_source.lock().unwrap() == FeedSource::Stream {
+                    self.tweets_feed.lock().unwrap().insert(0, tweet_id);
+                }
+                on_update();
+            })
+            .await
+    }
+
+    /// NB: the stream is pushed rather than paged, so there is never a next page.
+    pub async fn load_streamed_tweets(&self, restart: bool) -> Result<()> {
+        self.load_tweets_feed(
+            FeedSource::Stream,
+            move |_maybe_page_token| async move {
+                let streamed_tweets = self.streamed_tweets.lock().unwrap();
+                let tweets = self.tweets.lock().unwrap();
+                let streamed_tweets = streamed_tweets
+                    .iter()
+                    .filter_map(|tweet_id| tweets.get(tweet_id).cloned())
+                    .collect();
+                Ok((streamed_tweets, None))
             },
             restart,
         )
         .await
     }
 }
+
+/// Follows [load_page]'s pagination tokens to the end, for lists that are shown (or checked)
+/// whole rather than a page at a time.
+async fn load_all_pages<T, F, Fut>(load_page: F) -> Result<Vec<T>>
+where
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = PagedResult<Vec<T>>>,
+{
+    let mut items = Vec::new();
+    let mut page_token = None;
+    loop {
+        let (mut page, next_page_token) = load_page(page_token).await?;
+        items.append(&mut page);
+        match next_page_token {
+            Some(next_page_token) => page_token = Some(next_page_token),
+            None => return Ok(items),
+        }
+    }
+}
+
+/// Whether [tweet] is muted locally (see [UserConfig::is_muted]) or its author is muted or blocked
+/// on Twitter.  NB: for callers already holding both locks, which are taken in this order.
+pub fn is_muted_by(
+    user_config: &UserConfig,
+    muted_users: &HashSet<String>,
+    tweet: &api::Tweet,
+) -> bool {
+    muted_users.contains(&tweet.author_id) || user_config.is_muted(tweet)
+}
+
+/// NB: also moves [api::Tweet::included_tweets] into [tweets], without clobbering anything
+/// already there, and caches the tweets' authors in [users] (or fills them in from it).
+fn insert_tweet(
+    tweets: &mut HashMap<String, api::Tweet>,
+    users: &mut HashMap<String, api::User>,
+    mut tweet: api::Tweet,
+) {
+    for included_tweet in tweet.included_tweets.take().into_iter().flatten() {
+        if !tweets.contains_key(&included_tweet.id) {
+            insert_tweet(tweets, users, included_tweet);
+        }
+    }
+    match &tweet.author {
+        Some(author) => {
+            users.insert(author.id.clone(), author.clone());
+        }
+        None => {
+            if let Some(author) = users.get(&tweet.author_id) {
+                tweet.author = Some(author.clone());
+            }
+        }
+    }
+    tweets.insert(tweet.id.clone(), tweet);
+}
+
+/// Joins [terms] with `OR` into as few queries as fit in [max_len] each.
+fn split_or_query(terms: &[String], max_len: usize) -> Vec<String> {
+    let mut queries: Vec<String> = Vec::new();
+    for term in terms {
+        match queries.last_mut() {
+            Some(query) if query.len() + " OR ".len() + term.len() <= max_len => {
+                query.push_str(" OR ");
+                query.push_str(term);
+            }
+            _ => queries.push(term.clone()),
+        }
+    }
+    queries
+}
+
+/// NB: ids are snowflakes, so ordering them numerically orders them by time.
+fn id_key(id: &str) -> u64 {
+    id.parse().unwrap_or_default()
+}
+
+/// Merges one page of results of each of [queries] (indices into a
+/// [FeedSource::MergedSearch]), newest first, and works out where the next page starts.
+fn merge_search_results(
+    queries: &[usize],
+    results: Vec<(Vec<api::Tweet>, Option<String>)>,
+) -> (Vec<api::Tweet>, Option<MergedSearchPage>) {
+    let oldest_ids = results
+        .iter()
+        .map(|(tweets, _)| tweets.iter().map(|tweet| id_key(&tweet.id)).min())
+        .collect::<Vec<Option<u64>>>();
+    // NB: the oldest tweet every search with more results has reached
+    let horizon = results
+        .iter()
+        .zip(&oldest_ids)
+        .filter(|((_, next_token), _)| next_token.is_some())
+        .filter_map(|(_, oldest_id)| *oldest_id)
+        .max();
+
+    let next_page = horizon.map(|horizon| MergedSearchPage {
+        until_id: Some(horizon.to_string()),
+        queries: queries
+            .iter()
+            .zip(results.iter().zip(&oldest_ids))
+            .filter(|(_, ((_, next_token), oldest_id))| {
+                next_token.is_some() || oldest_id.is_some_and(|oldest_id| oldest_id < horizon)
+            })
+            .map(|(index, _)| *index)
+            .collect(),
+    });
+
+    let tweets = results
+        .into_iter()
+        .flat_map(|(tweets, _)| tweets)
+        .filter(|tweet| horizon.is_none_or(|horizon| id_key(&tweet.id) >= horizon))
+        .unique_by(|tweet| tweet.id.clone())
+        .sorted_by_key(|tweet| std::cmp::Reverse(id_key(&tweet.id)))
+        .collect();
+    (tweets, next_page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter_client::mock_server::{MockResponse, MockServer};
+    use crate::twitter_client::{RetryPolicy, TwitterApiError, TwitterEndpoints};
+    use hyper::{Method, StatusCode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn mock_store(mock_server: &MockServer) -> Store {
+        let endpoints = TwitterEndpoints::with_base_url(&mock_server.base_url()).unwrap();
+        let mut twitter_client = TwitterClient::new_with_endpoints("id", "secret", endpoints);
+        twitter_client.set_access_token("token");
+        let twitter_user = api::User {
+            id: "1000".to_string(),
+            name: "Mock User".to_string(),
+            username: "mock_user".to_string(),
+            ..api::User::default()
+        };
+        Store::new(twitter_client, &twitter_user, &UserConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_load_tweets_feed_from_fixtures() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+
+        let feed = store.tweets_feed.lock().unwrap().clone();
+        assert_eq!(feed, vec!["1600000000000000002", "1600000000000000001"]);
+        let tweets = store.tweets.lock().unwrap();
+        let tweet = &tweets["1600000000000000002"];
+        assert_eq!(tweet.author_username(), Some("jack"));
+
+        let requests = mock_server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].url.path(),
+            "/2/users/1000/timelines/reverse_chronological"
+        );
+        assert_eq!(requests[0].headers["authorization"], "Bearer token");
+    }
+
+    #[tokio::test]
+    async fn test_load_tweets_feed_next_page() {
+        let mock_server = MockServer::start().unwrap();
+        let path = "/2/users/1000/timelines/reverse_chronological";
+        let page = |id: &str, next_token: Option<&str>| {
+            let body = serde_json::json!({
+                "data": [{
+                    "id": id,
+                    "text": "hello",
+                    "created_at": "2023-02-01T12:00:00.000Z",
+                    "author_id": "1000",
+                }],
+                "includes": { "users": [{ "id": "1000", "name": "Mock User", "username": "mock_user" }] },
+                "meta": { "result_count": 1, "next_token": next_token },
+            });
+            MockResponse::json(&body.to_string())
+        };
+        mock_server.route(
+            Method::GET,
+            path,
+            &[("pagination_token", "page2")],
+            vec![page("1", None)],
+        );
+        mock_server.route(Method::GET, path, &[], vec![page("2", Some("page2"))]);
+        let store = mock_store(&mock_server);
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        store
+            .load_tweets_reverse_chronological(false)
+            .await
+            .unwrap();
+        assert_eq!(*store.tweets_feed.lock().unwrap(), vec!["2", "1"]);
+        assert!(store
+            .load_tweets_reverse_chronological(false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_page_replaces_feed() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        store.load_user_tweets("1001", true).await.unwrap();
+        assert!(store.tweets_feed.lock().unwrap().is_empty());
+        assert!(store.load_tweets_feed_next_page().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_reply() {
+        let mock_server = MockServer::start().unwrap();
+        let body =
+            r#"{"data":{"edit_history_tweet_ids":["1700"],"id":"1700","text":"@jack me too"}}"#;
+        mock_server.route(
+            Method::POST,
+            "/2/tweets",
+            &[],
+            vec![MockResponse::json(body).with_status(hyper::StatusCode::CREATED)],
+        );
+        let store = mock_store(&mock_server);
+
+        let new_tweet = api::NewTweet {
+            text: "@jack me too".to_string(),
+            reply: Some(api::NewTweetReply {
+                in_reply_to_tweet_id: "20".to_string(),
+            }),
+            quote_tweet_id: None,
+        };
+        assert_eq!(store.create_tweet(&new_tweet).await.unwrap(), "1700");
+
+        let request = &mock_server.requests()[0];
+        let request_body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            request_body,
+            serde_json::json!({ "text": "@jack me too", "reply": { "in_reply_to_tweet_id": "20" } })
+        );
+        let tweets = store.tweets.lock().unwrap();
+        let tweet = &tweets["1700"];
+        assert_eq!(tweet.author_id, "1000");
+        assert_eq!(tweet.referenced_tweets.as_ref().unwrap()[0].id, "20");
+        assert_eq!(*store.tweets_feed.lock().unwrap(), vec!["1700"]);
+        drop(tweets);
+
+        // NB: not pushed into feeds it doesn't belong to
+        *store.tweets_feed_source.lock().unwrap() = FeedSource::Bookmarks;
+        store.create_tweet(&new_tweet).await.unwrap();
+        assert_eq!(*store.tweets_feed.lock().unwrap(), vec!["1700"]);
+    }
+
+    #[tokio::test]
+    async fn test_engagement_reverts_on_failure() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let forbidden =
+            r#"{"title":"Forbidden","type":"about:blank","status":403,"detail":"Forbidden"}"#;
+        mock_server.route(
+            Method::POST,
+            "/2/users/1000/likes",
+            &[],
+            vec![
+                MockResponse::json(r#"{"data":{"liked":true}}"#),
+                MockResponse::json(forbidden).with_status(hyper::StatusCode::FORBIDDEN),
+            ],
+        );
+        let store = mock_store(&mock_server);
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        let tweet_id = "1600000000000000002";
+        let like_count = |store: &Store| {
+            let tweets = store.tweets.lock().unwrap();
+            tweets[tweet_id].public_metrics.as_ref().unwrap().like_count
+        };
+
+        let engaged = store.toggle_engagement(Engagement::Like, tweet_id);
+        assert!(engaged && store.is_engaged(Engagement::Like, tweet_id));
+        assert_eq!(like_count(&store), 101);
+        store
+            .sync_engagement(Engagement::Like, tweet_id, engaged)
+            .await
+            .unwrap();
+        let request = mock_server.requests().pop().unwrap();
+        assert_eq!(&request.body[..], br#"{"tweet_id":"1600000000000000002"}"#);
+
+        store.set_engaged(Engagement::Like, tweet_id, false);
+        let engaged = store.toggle_engagement(Engagement::Like, tweet_id);
+        assert!(store
+            .sync_engagement(Engagement::Like, tweet_id, engaged)
+            .await
+            .is_err());
+        assert!(!store.is_engaged(Engagement::Like, tweet_id));
+        assert_eq!(like_count(&store), 100);
+    }
+
+    #[tokio::test]
+    async fn test_bookmarks_feed_pages() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+
+        store.load_bookmarks(true).await.unwrap();
+        assert_eq!(
+            *store.tweets_feed_source.lock().unwrap(),
+            FeedSource::Bookmarks
+        );
+        assert!(store.is_engaged(Engagement::Bookmark, "1600000000000000002"));
+        store.load_tweets_feed_next_page().await.unwrap();
+
+        let requests = mock_server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].url.path(), "/2/users/1000/bookmarks");
+        let pagination_token = requests[1]
+            .url
+            .query_pairs()
+            .find(|(key, _)| key == "pagination_token")
+            .map(|(_, value)| value.to_string());
+        assert_eq!(pagination_token.as_deref(), Some("bookmarks_page_2"));
+    }
+
+    #[tokio::test]
+    async fn test_list_feed() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/owned_lists",
+            &[("pagination_token", "owned_lists_page_2")],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"2002","name":"Friends","private":true}],"meta":{"result_count":1}}"#,
+            )],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/owned_lists",
+            &[],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"2000","name":"Rustaceans"}],"meta":{"result_count":1,"next_token":"owned_lists_page_2"}}"#,
+            )],
+        );
+        let store = mock_store(&mock_server);
+
+        store.load_lists().await.unwrap();
+        let list_names = store
+            .lists
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|list| list.name.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(list_names, vec!["Rustaceans", "Friends", "News"]);
+
+        store.load_list_tweets("2000", true).await.unwrap();
+        assert_eq!(
+            *store.tweets_feed_source.lock().unwrap(),
+            FeedSource::List("2000".to_string())
+        );
+        store.load_tweets_feed_next_page().await.unwrap();
+        let last_request = mock_server.requests().pop().unwrap();
+        assert_eq!(last_request.url.path(), "/2/lists/2000/tweets");
+    }
+
+    #[tokio::test]
+    async fn test_mentions_feed_pages() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+
+        store.load_feed(&FeedSource::Mentions, true).await.unwrap();
+        assert_eq!(store.tweets_feed.lock().unwrap().len(), 1);
+        store.load_tweets_feed_next_page().await.unwrap();
+
+        let last_request = mock_server.requests().pop().unwrap();
+        assert_eq!(last_request.url.path(), "/2/users/1000/mentions");
+        assert!(last_request
+            .url
+            .query_pairs()
+            .any(|(key, value)| key == "pagination_token" && value == "mentions_page_2"));
+    }
+
+    #[tokio::test]
+    async fn test_search_feed_pages_within_window() {
+        let mock_server = MockServer::start().unwrap();
+        let path = "/2/tweets/search/recent";
+        let page = |id: &str, next_token: Option<&str>| {
+            let body = serde_json::json!({
+                "data": [{
+                    "id": id,
+                    "text": "#rust",
+                    "created_at": "2023-02-01T12:00:00.000Z",
+                    "author_id": "1000",
+                }],
+                "includes": { "users": [{ "id": "1000", "name": "Mock User", "username": "mock_user" }] },
+                "meta": { "result_count": 1, "next_token": next_token },
+            });
+            MockResponse::json(&body.to_string())
+        };
+        mock_server.route(
+            Method::GET,
+            path,
+            &[("next_token", "search_page_2")],
+            vec![page("1", None)],
+        );
+        mock_server.route(
+            Method::GET,
+            path,
+            &[],
+            vec![page("2", Some("search_page_2"))],
+        );
+        let store = mock_store(&mock_server);
+
+        let source = FeedSource::Search {
+            query: "#rust".to_string(),
+            window: api::SearchWindow {
+                since_id: Some("1".to_string()),
+                end_time: Some("2023-02-02T00:00:00Z".parse().unwrap()),
+                ..api::SearchWindow::default()
+            },
+        };
+        store.load_feed(&source, true).await.unwrap();
+        store.load_tweets_feed_next_page().await.unwrap();
+        assert_eq!(*store.tweets_feed.lock().unwrap(), vec!["2", "1"]);
+
+        let last_request = mock_server.requests().pop().unwrap();
+        let query = last_request
+            .url
+            .query_pairs()
+            .into_owned()
+            .collect::<HashMap<String, String>>();
+        assert_eq!(query["query"], "#rust");
+        assert_eq!(query["since_id"], "1");
+        assert_eq!(query["end_time"], "2023-02-02T00:00:00Z");
+        assert!(!query.contains_key("pagination_token"));
+    }
+
+    #[test]
+    fn test_split_or_query() {
+        let terms = ["from:1", "from:22", "from:333"].map(String::from);
+        assert_eq!(
+            split_or_query(&terms, 20),
+            vec!["from:1 OR from:22", "from:333"]
+        );
+        assert_eq!(
+            split_or_query(&terms, MAX_SEARCH_QUERY_LEN),
+            vec!["from:1 OR from:22 OR from:333"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merged_search_feed_pages() {
+        let mock_server = MockServer::start().unwrap();
+        let path = "/2/tweets/search/recent";
+        let page = |ids: &[&str], next_token: Option<&str>| {
+            let data = ids
+                .iter()
+                .map(|id| {
+                    serde_json::json!({
+                        "id": id,
+                        "text": "hello",
+                        "created_at": "2023-02-01T12:00:00.000Z",
+                        "author_id": "1000",
+                    })
+                })
+                .collect::<Vec<_>>();
+            let body = serde_json::json!({
+                "data": data,
+                "includes": { "users": [{ "id": "1000", "name": "Mock User", "username": "mock_user" }] },
+                "meta": { "result_count": ids.len(), "next_token": next_token },
+            });
+            MockResponse::json(&body.to_string())
+        };
+        let from_1 = [("query", "from:1"), ("until_id", "8")];
+        let from_2 = [("query", "from:2"), ("until_id", "8")];
+        mock_server.route(Method::GET, path, &from_1, vec![page(&["6"], None)]);
+        mock_server.route(Method::GET, path, &from_2, vec![page(&["5"], None)]);
+        mock_server.route(
+            Method::GET,
+            path,
+            &from_1[..1],
+            vec![page(&["10", "8"], Some("more"))],
+        );
+        mock_server.route(
+            Method::GET,
+            path,
+            &from_2[..1],
+            vec![page(&["9", "5"], None)],
+        );
+        let store = mock_store(&mock_server);
+
+        // NB: `5` waits for the next page, since `from:1` may have tweets between it and `8`
+        let queries = vec!["from:1".to_string(), "from:2".to_string()];
+        store
+            .load_merged_search_tweets(&queries, true)
+            .await
+            .unwrap();
+        assert_eq!(*store.tweets_feed.lock().unwrap(), vec!["10", "9", "8"]);
+
+        store.load_tweets_feed_next_page().await.unwrap();
+        assert_eq!(
+            *store.tweets_feed.lock().unwrap(),
+            vec!["10", "9", "8", "6", "5"]
+        );
+        assert!(store.load_tweets_feed_next_page().await.is_err());
+        assert_eq!(mock_server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_user_list_pages_and_follow() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        mock_server.route(
+            Method::GET,
+            "/2/users/12/followers",
+            &[("pagination_token", "followers_page_2")],
+            vec![MockResponse::json(r#"{"meta":{"result_count":0}}"#)],
+        );
+        mock_server.route(
+            Method::POST,
+            "/2/users/1000/following",
+            &[],
+            vec![
+                MockResponse::json(r#"{"data":{"following":true,"pending_follow":false}}"#),
+                MockResponse::json(r#"{"data":{"following":false,"pending_follow":true}}"#),
+            ],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/following",
+            &[("pagination_token", "following_page_2")],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"14","name":"Fourteen","username":"fourteen"}],"meta":{"result_count":1}}"#,
+            )],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/following",
+            &[],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"12","name":"jack","username":"jack"}],"meta":{"result_count":1,"next_token":"following_page_2"}}"#,
+            )],
+        );
+        mock_server.route(
+            Method::DELETE,
+            "/2/users/1000/following/13",
+            &[],
+            vec![MockResponse::json("{}").with_status(StatusCode::FORBIDDEN)],
+        );
+        let store = mock_store(&mock_server);
+
+        let source = UserListSource::Followers("12".to_string());
+        store.load_user_list(&source, true).await.unwrap();
+        assert_eq!(*store.user_list.lock().unwrap(), vec!["13", "1000"]);
+        assert!(store.users.lock().unwrap()["13"].public_metrics.is_some());
+        store.load_user_list(&source, false).await.unwrap();
+        assert_eq!(store.user_list.lock().unwrap().len(), 2);
+        assert!(store.load_user_list(&source, false).await.is_err());
+
+        // NB: can't tell which way to toggle until we know who we follow
+        assert!(store.toggle_following("13").is_err());
+        store.load_followed_users().await.unwrap();
+        assert!(store.is_following("12") && store.is_following("14"));
+
+        assert!(store.toggle_following("13").unwrap());
+        store.sync_following("13", true).await.unwrap();
+        assert!(!store.toggle_following("13").unwrap());
+        store.sync_following("13", false).await.unwrap_err();
+        assert!(store.is_following("13"));
+
+        assert!(store.toggle_following("15").unwrap());
+        store.sync_following("15", true).await.unwrap();
+        assert!(!store.is_following("15"));
+        assert!(store.is_pending_follow("15"));
+    }
+
+    #[tokio::test]
+    async fn test_tweet_counts_are_cached() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let mut store = mock_store(&mock_server);
+
+        // NB: not even tried without an app token
+        store
+            .load_tweet_counts("12", api::Granularity::Day)
+            .await
+            .unwrap();
+        assert!(mock_server.requests().is_empty());
+
+        store.twitter_client.set_app_access_token("app_token");
+        store
+            .load_tweet_counts("12", api::Granularity::Day)
+            .await
+            .unwrap();
+        store
+            .load_tweet_counts("12", api::Granularity::Day)
+            .await
+            .unwrap();
+
+        let tweet_counts = store.tweet_counts.lock().unwrap();
+        let tweet_counts = &tweet_counts[&("12".to_string(), api::Granularity::Day)];
+        assert_eq!(tweet_counts.len(), 7);
+        assert_eq!(tweet_counts[6].tweet_count, 4);
+        let requests = mock_server.requests();
+        assert_eq!(requests.len(), 1);
+        let query = requests[0].url.query_pairs().collect::<HashMap<_, _>>();
+        assert_eq!(query["query"], "from:12");
+        assert_eq!(query["granularity"], "day");
+    }
+
+    #[tokio::test]
+    async fn test_tweet_counts_failures_are_cached() {
+        let mock_server = MockServer::start().unwrap();
+        mock_server.route(
+            Method::GET,
+            "/2/tweets/counts/recent",
+            &[],
+            vec![MockResponse::json("{}").with_status(StatusCode::FORBIDDEN)],
+        );
+        let mut store = mock_store(&mock_server);
+        store.twitter_client.set_app_access_token("app_token");
+
+        assert!(store
+            .load_tweet_counts("12", api::Granularity::Day)
+            .await
+            .is_err());
+        store
+            .load_tweet_counts("12", api::Granularity::Hour)
+            .await
+            .unwrap();
+        assert_eq!(mock_server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_muted_users() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/muting",
+            &[("pagination_token", "muting_page_2")],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"14","name":"ev","username":"ev"}],"meta":{"result_count":1}}"#,
+            )],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/muting",
+            &[],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"12","name":"jack","username":"jack"}],"meta":{"result_count":1,"next_token":"muting_page_2"}}"#,
+            )],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/blocking",
+            &[],
+            vec![MockResponse::json("{}").with_status(StatusCode::FORBIDDEN)],
+        );
+        let store = mock_store(&mock_server);
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        let tweet = store.tweets.lock().unwrap()["1600000000000000002"].clone();
+        assert!(!store.is_muted(&tweet));
+
+        store.load_muted_users().await.unwrap();
+        let muted_users = store.muted_users.lock().unwrap().clone();
+        assert_eq!(
+            muted_users,
+            HashSet::from(["12".to_string(), "14".to_string()])
+        );
+        assert!(store.is_muted(&tweet));
+
+        store.muted_users.lock().unwrap().clear();
+        assert!(!store.is_muted(&tweet));
+        store.user_config.lock().unwrap().muted_keywords = vec!["TWTTR".to_string()];
+        assert!(store.is_muted(&tweet));
+    }
+
+    #[tokio::test]
+    async fn test_mute_on_twitter() {
+        let mock_server = MockServer::start().unwrap();
+        mock_server.route(
+            Method::POST,
+            "/2/users/1000/muting",
+            &[],
+            vec![MockResponse::json(r#"{"data":{"muting":true}}"#)],
+        );
+        mock_server.route(
+            Method::DELETE,
+            "/2/users/1000/muting/12",
+            &[],
+            vec![MockResponse::json("{}").with_status(StatusCode::FORBIDDEN)],
+        );
+        let store = mock_store(&mock_server);
+
+        assert!(store.toggle_muted_user("12"));
+        store.sync_muted_user("12", true).await.unwrap();
+        assert!(!store.toggle_muted_user("12"));
+        store.sync_muted_user("12", false).await.unwrap_err();
+        assert!(store.muted_users.lock().unwrap().contains("12"));
+    }
+
+    #[tokio::test]
+    async fn test_muted_users_keep_what_loaded() {
+        let mock_server = MockServer::start().unwrap();
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/muting",
+            &[],
+            vec![MockResponse::json("{}").with_status(StatusCode::BAD_REQUEST)],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users/1000/blocking",
+            &[],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"13","name":"biz","username":"biz"}],"meta":{"result_count":1}}"#,
+            )],
+        );
+        let store = mock_store(&mock_server);
+
+        assert!(store.load_muted_users().await.is_err());
+        let muted_users = store.muted_users.lock().unwrap().clone();
+        assert_eq!(muted_users, HashSet::from(["13".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_dm_conversations() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        mock_server.route(
+            Method::POST,
+            "/2/dm_conversations/1000-12/messages",
+            &[],
+            vec![MockResponse::json(
+                r#"{"data":{"dm_conversation_id":"1000-12","dm_event_id":"1600000000000000104"}}"#,
+            )],
+        );
+        let store = mock_store(&mock_server);
+
+        store.load_dm_events().await.unwrap();
+        {
+            let dm_conversations = store.dm_conversations.lock().unwrap();
+            let ids = dm_conversations
+                .iter()
+                .map(|dm_conversation| dm_conversation.id.as_str())
+                .collect::<Vec<&str>>();
+            assert_eq!(ids, vec!["1000-12", "1600000000000000100"]);
+            assert_eq!(dm_conversations[0].participant_ids, vec!["12", "1000"]);
+            assert_eq!(
+                dm_conversations[1].participant_ids,
+                vec!["12", "13", "14", "1000"]
+            );
+            // NB: including those who've joined but never said anything
+            let users = store.users.lock().unwrap();
+            assert_eq!(users["13"].username, "biz");
+            assert_eq!(users["14"].username, "ev");
+        }
+
+        store.load_dm_conversation("1000-12").await.unwrap();
+        store.send_dm("1000-12", "on my way").await.unwrap();
+        let dm_events = store.dm_events.lock().unwrap();
+        let texts = dm_events["1000-12"]
+            .iter()
+            .filter_map(|dm_event| dm_event.text.as_deref())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            texts,
+            vec![
+                "from last week",
+                "morning &amp; welcome",
+                "standup in 5",
+                "on my way"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_referenced_tweets() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+        mock_server.route(
+            Method::GET,
+            "/2/tweets",
+            &[("ids", "1600000000000000000")],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"1600000000000000000","text":"root","created_at":"2023-02-01T11:00:00.000Z","author_id":"1000"}],"includes":{"users":[{"id":"1000","name":"Mock User","username":"mock_user"}]}}"#,
+            )],
+        );
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        let feed = store.tweets_feed.lock().unwrap().clone();
+        // NB: the later callers wait for the first one's lookup rather than skipping the tweet
+        tokio::try_join!(
+            store.load_referenced_tweets(&feed),
+            store.load_referenced_tweets(&feed),
+            async {
+                store.load_tweet("1600000000000000000").await?;
+                assert!(store
+                    .tweets
+                    .lock()
+                    .unwrap()
+                    .contains_key("1600000000000000000"));
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        let tweets = store.tweets.lock().unwrap();
+        let root = tweets.get("1600000000000000000").unwrap();
+        assert_eq!(root.author_username(), Some("mock_user"));
+        let lookups = mock_server
+            .requests()
+            .into_iter()
+            .filter(|request| request.url.path() == "/2/tweets")
+            .count();
+        assert_eq!(lookups, 1);
+        assert!(store.tweets_in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_load_releases_tweets_in_flight() {
+        let mock_server = MockServer::start().unwrap();
+        let store = mock_store(&mock_server);
+        mock_server.route(
+            Method::GET,
+            "/2/tweets",
+            &[],
+            vec![MockResponse::json("{}").stalled()],
+        );
+
+        let timeout = std::time::Duration::from_millis(100);
+        let load = tokio::time::timeout(timeout, store.load_tweet("1"));
+        assert!(load.await.is_err());
+        assert!(store.tweets_in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_authors_are_looked_up_once() {
+        let mock_server = MockServer::start().unwrap();
+        let store = mock_store(&mock_server);
+        for id in ["1", "2"] {
+            let body = format!(
+                r#"{{"data":[{{"id":"{id}","text":"hi","created_at":"2023-02-01T11:00:00.000Z","author_id":"12"}}]}}"#
+            );
+            mock_server.route(
+                Method::GET,
+                "/2/tweets",
+                &[("ids", id)],
+                vec![MockResponse::json(&body)],
+            );
+        }
+        mock_server.route(
+            Method::GET,
+            "/2/users",
+            &[("ids", "12")],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"12","name":"jack","username":"jack","verified":true,"public_metrics":{"followers_count":1,"following_count":2,"tweet_count":3,"listed_count":4}}]}"#,
+            )],
+        );
+
+        store.load_tweets(&["1".to_string()]).await.unwrap();
+        store.load_tweets(&["2".to_string()]).await.unwrap();
+        let user = store.user_by_username("JACK").await.unwrap();
+        assert_eq!(user.public_metrics.unwrap().followers_count, 1);
+
+        let tweets = store.tweets.lock().unwrap();
+        assert_eq!(tweets["2"].author_username(), Some("jack"));
+        let user_lookups = mock_server
+            .requests()
+            .into_iter()
+            .filter(|request| request.url.path().starts_with("/2/users"))
+            .count();
+        assert_eq!(user_lookups, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_author_lookup_is_a_warning() {
+        let mock_server = MockServer::start().unwrap();
+        let store = mock_store(&mock_server);
+        mock_server.route(
+            Method::GET,
+            "/2/tweets",
+            &[],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"1","text":"hi","created_at":"2023-02-01T11:00:00.000Z","author_id":"12"}]}"#,
+            )],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/users",
+            &[],
+            vec![MockResponse::json("{}").with_status(StatusCode::FORBIDDEN)],
+        );
+
+        store.load_tweets(&["1".to_string()]).await.unwrap();
+        let tweets = store.tweets.lock().unwrap();
+        assert_eq!(tweets["1"].author_username(), None);
+        let warnings = store.twitter_client.warnings().recent;
+        assert_eq!(warnings[0].title.as_deref(), Some("Author lookup failed"));
+    }
+
+    #[tokio::test]
+    async fn test_restart_clears_warnings() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+
+        store.load_user_tweets("1002", true).await.unwrap();
+        let warnings = store.twitter_client.warnings();
+        assert_eq!(warnings.count, 1);
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        let restarted = store.twitter_client.warnings();
+        assert_eq!(restarted.count, 0);
+        assert!(restarted.generation > warnings.generation);
+    }
+
+    #[tokio::test]
+    async fn test_load_thread_and_replies() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+        mock_server.route(
+            Method::GET,
+            "/2/tweets",
+            &[("ids", "1600000000000000000")],
+            vec![MockResponse::json(
+                r#"{"data":[{"id":"1600000000000000000","text":"root","created_at":"2023-02-01T11:00:00.000Z","author_id":"1000","conversation_id":"1600000000000000000"}],"includes":{"users":[{"id":"1000","name":"Mock User","username":"mock_user"}]}}"#,
+            )],
+        );
+        mock_server.route(
+            Method::GET,
+            "/2/tweets/search/recent",
+            &[("query", "conversation_id:1600000000000000000")],
+            vec![MockResponse::json(
+                r#"{"data":[
+                    {"id":"1600000000000000005","text":"nested","created_at":"2023-02-01T12:10:00.000Z","author_id":"12","conversation_id":"1600000000000000000","referenced_tweets":[{"type":"replied_to","id":"1600000000000000001"}]},
+                    {"id":"1600000000000000004","text":"second","created_at":"2023-02-01T12:05:00.000Z","author_id":"12","conversation_id":"1600000000000000000","referenced_tweets":[{"type":"replied_to","id":"1600000000000000000"}]},
+                    {"id":"1600000000000000001","text":"first","created_at":"2023-02-01T12:00:00.000Z","author_id":"1000","conversation_id":"1600000000000000000","referenced_tweets":[{"type":"replied_to","id":"1600000000000000000"}]}
+                ],"includes":{"users":[{"id":"12","name":"jack","username":"jack"}]}}"#,
+            )],
+        );
+
+        store.load_tweets_reverse_chronological(true).await.unwrap();
+        let thread = store.load_thread("1600000000000000001").await.unwrap();
+        assert_eq!(thread, vec!["1600000000000000000"]);
+
+        let replies = store.load_replies("1600000000000000000").await.unwrap();
+        assert_eq!(replies, vec!["1600000000000000001", "1600000000000000004"]);
+        assert!(store
+            .tweets
+            .lock()
+            .unwrap()
+            .contains_key("1600000000000000005"));
+    }
+
+    #[tokio::test]
+    async fn test_follow_stream_reconnects() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let store = mock_store(&mock_server);
+        let store = Store {
+            twitter_client: store.twitter_client.with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(10),
+            }),
+            ..store
+        };
+        let stream = MockResponse::stream_fixture("fixtures/2/tweets/search/stream.ndjson");
+        mock_server.route(
+            Method::GET,
+            "/2/tweets/search/stream",
+            &[],
+            vec![
+                stream.unwrap(),
+                MockResponse::json(r#"{"title":"Service Unavailable"}"#)
+                    .with_status(StatusCode::SERVICE_UNAVAILABLE),
+            ],
+        );
+
+        store.load_streamed_tweets(true).await.unwrap();
+        let updates = AtomicUsize::new(0);
+        let error = store
+            .follow_stream(|| {
+                updates.fetch_add(1, Ordering::SeqCst);
+            })
+            .await
+            .unwrap_err();
+
+        let error = error.downcast_ref::<TwitterApiError>().unwrap();
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(updates.load(Ordering::SeqCst), 2);
+        let warnings = store.twitter_client.warnings().recent;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].title.as_deref(),
+            Some("Undecodable stream line")
+        );
+        assert_eq!(
+            *store.tweets_feed.lock().unwrap(),
+            vec!["1600000000000000011", "1600000000000000010"]
+        );
+        let connections = mock_server
+            .requests()
+            .into_iter()
+            .filter(|request| request.url.path() == "/2/tweets/search/stream")
+            .count();
+        assert_eq!(connections, 2);
+    }
+}
@@ -1,26 +1,107 @@
 use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
 
+/// NB: `data` is omitted entirely when there's nothing to return (e.g. an empty page), and may
+/// come along with `errors` for whatever part of the request couldn't be fulfilled.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Response<Data, Includes> {
-    pub data: Data,
+    pub data: Option<Data>,
     pub includes: Option<Includes>,
     pub meta: Option<Meta>,
+    pub errors: Option<Vec<Error>>,
+}
+
+/// Body of a non-success response (RFC 7807 problem details), or of a success response that only
+/// carries `errors`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Problem {
+    pub title: Option<String>,
+    pub detail: Option<String>,
+    pub r#type: Option<String>,
+    pub status: Option<u16>,
+    pub errors: Option<Vec<Error>>,
+}
+
+/// NB: the v2 API is inconsistent about which of these are present; partial errors (e.g. one
+/// missing user in a batch lookup) carry [title]/[detail]/[resource_id], while request validation
+/// errors carry only [message].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Error {
+    pub title: Option<String>,
+    pub detail: Option<String>,
+    pub r#type: Option<String>,
+    pub message: Option<String>,
+    pub parameter: Option<String>,
+    pub value: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+}
+
+impl Error {
+    /// For problems noticed on our side, e.g. a line of the stream that couldn't be decoded.
+    pub fn new(title: &str, detail: &str) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            detail: Some(detail.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        self.detail
+            .as_deref()
+            .or(self.message.as_deref())
+            .or(self.title.as_deref())
+            .unwrap_or("unknown error")
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Meta {
     pub next_token: Option<String>,
+    // NB: absent from e.g. the stream rules endpoints
+    #[serde(default)]
     pub result_count: i64,
     pub newest_id: Option<String>,
     pub oldest_id: Option<String>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub name: String,
     pub username: String,
+    // NB: only sent when asked for in `user.fields`, see [USER_FIELDS]
+    pub description: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    pub verified: Option<bool>,
+    pub protected: Option<bool>,
+    pub pinned_tweet_id: Option<String>,
+    pub profile_image_url: Option<String>,
+    pub public_metrics: Option<UserPublicMetrics>,
+}
+
+/// The `user.fields` that fill in the optional parts of [User].
+pub const USER_FIELDS: &str =
+    "created_at,description,location,pinned_tweet_id,profile_image_url,protected,public_metrics,url,verified";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UserPublicMetrics {
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub tweet_count: i64,
+    pub listed_count: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct List {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub private: Option<bool>,
+    pub owner_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,30 +110,170 @@ pub struct Tweet {
     pub text: String,
     pub created_at: DateTime<Local>,
     pub author_id: String,
-    pub author_username: Option<String>,
-    pub author_name: Option<String>,
+    /// Resolved from `includes`, or from [Store::users] when the response left it out.
+    ///
+    /// [Store::users]: crate::store::Store::users
+    pub author: Option<User>,
     pub conversation_id: Option<String>,
     pub referenced_tweets: Option<Vec<TweetReference>>,
     pub attachments: Option<Attachments>,
     pub public_metrics: Option<PublicMetrics>,
+    pub entities: Option<Entities>,
+    /// Only on tweets longer than 280 characters, whose [text] is then truncated.
+    pub note_tweet: Option<NoteTweet>,
+    pub geo: Option<Geo>,
+    // NB: the rest are resolved from the response's `includes` rather than sent inline
+    pub media: Option<Vec<Media>>,
+    pub poll: Option<Poll>,
+    pub place: Option<Place>,
+    /// The tweets that [referenced_tweets] refers to, as far as they were included; these are
+    /// moved into [Store::tweets] on load.
+    ///
+    /// [Store::tweets]: crate::store::Store::tweets
+    pub included_tweets: Option<Vec<Tweet>>,
 }
 
 impl Tweet {
+    /// The untruncated text, along with the entities that go with it.
+    pub fn full_text(&self) -> (&str, Option<&Entities>) {
+        match &self.note_tweet {
+            Some(note_tweet) => (&note_tweet.text, note_tweet.entities.as_ref()),
+            None => (&self.text, self.entities.as_ref()),
+        }
+    }
+
+    pub fn author_username(&self) -> Option<&str> {
+        self.author.as_ref().map(|author| author.username.as_str())
+    }
+
+    pub fn author_name(&self) -> Option<&str> {
+        self.author.as_ref().map(|author| author.name.as_str())
+    }
+
     pub fn author(&self, fill_unknown_with: &str) -> User {
+        if let Some(author) = &self.author {
+            return author.clone();
+        }
         User {
             id: self.author_id.clone(),
-            name: self
-                .author_name
-                .clone()
-                .unwrap_or(fill_unknown_with.to_string()),
-            username: self
-                .author_username
-                .clone()
-                .unwrap_or(fill_unknown_with.to_string()),
+            name: fill_unknown_with.to_string(),
+            username: fill_unknown_with.to_string(),
+            ..User::default()
+        }
+    }
+}
+
+/// Body of `POST /2/tweets`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NewTweet {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<NewTweetReply>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_tweet_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NewTweetReply {
+    pub in_reply_to_tweet_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreatedTweet {
+    pub id: String,
+    pub text: String,
+}
+
+/// NB: following a protected account only sends a request, reported as `pending_follow`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FollowStatus {
+    pub following: bool,
+    pub pending_follow: Option<bool>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DmEvent {
+    pub id: String,
+    /// `MessageCreate`, `ParticipantsJoin` or `ParticipantsLeave`.
+    pub event_type: String,
+    pub text: Option<String>,
+    pub sender_id: Option<String>,
+    pub dm_conversation_id: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+    // NB: only on `ParticipantsJoin`/`ParticipantsLeave`
+    pub participant_ids: Option<Vec<String>>,
+    // NB: resolved from the response's `includes` rather than sent inline
+    pub sender: Option<User>,
+    #[serde(default)]
+    pub participants: Vec<User>,
+}
+
+/// NB: the API has no endpoint for listing these, so they're put together from [DmEvent]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DmConversation {
+    pub id: String,
+    pub participant_ids: Vec<String>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreatedDm {
+    pub dm_conversation_id: String,
+    pub dm_event_id: String,
+}
+
+/// A filtered stream rule, as reported back by the API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamRule {
+    pub id: String,
+    pub value: String,
+    pub tag: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewStreamRule {
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// Restricts a search to tweets between ids and/or times, on top of its query.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchWindow {
+    pub since_id: Option<String>,
+    pub until_id: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+/// How finely [TweetCount]s are bucketed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Granularity {
+    Minute,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Granularity::Minute => "minute",
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
         }
     }
 }
 
+/// The number of tweets matching a query between [start] and [end], see
+/// [TwitterClient::tweet_counts].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TweetCount {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub tweet_count: i64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TweetReference {
     pub r#type: String,
@@ -65,6 +286,92 @@ pub struct Attachments {
     pub media_keys: Option<Vec<String>>,
 }
 
+/// NB: [start]/[end] are code point offsets into the tweet's text, which is still HTML-escaped.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Entities {
+    pub urls: Option<Vec<UrlEntity>>,
+    pub mentions: Option<Vec<MentionEntity>>,
+    pub hashtags: Option<Vec<TagEntity>>,
+    pub cashtags: Option<Vec<TagEntity>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UrlEntity {
+    pub start: usize,
+    pub end: usize,
+    /// The `t.co` link as it appears in the text.
+    pub url: String,
+    pub expanded_url: Option<String>,
+    pub display_url: Option<String>,
+    pub media_key: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MentionEntity {
+    pub start: usize,
+    pub end: usize,
+    pub username: String,
+    pub id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagEntity {
+    pub start: usize,
+    pub end: usize,
+    pub tag: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteTweet {
+    pub text: String,
+    pub entities: Option<Entities>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Geo {
+    pub place_id: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Media {
+    pub media_key: String,
+    /// `photo`, `animated_gif` or `video`
+    pub r#type: String,
+    pub url: Option<String>,
+    pub preview_image_url: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub alt_text: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Poll {
+    pub id: String,
+    pub options: Vec<PollOption>,
+    pub duration_minutes: Option<u32>,
+    pub end_datetime: Option<DateTime<Utc>>,
+    /// `open` or `closed`
+    pub voting_status: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PollOption {
+    pub position: u32,
+    pub label: String,
+    pub votes: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Place {
+    pub id: String,
+    pub full_name: String,
+    pub name: Option<String>,
+    pub country: Option<String>,
+    pub country_code: Option<String>,
+    pub place_type: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PublicMetrics {
     pub retweet_count: i32,
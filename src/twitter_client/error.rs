@@ -0,0 +1,138 @@
+use crate::twitter_client::api;
+use chrono::{DateTime, Local, Utc};
+use hyper::StatusCode;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// NB: carried inside [anyhow::Error] through [Store], so callers that care about a specific
+/// failure should [anyhow::Error::downcast_ref] to this.
+///
+/// [Store]: crate::store::Store
+#[derive(Debug)]
+pub enum TwitterApiError {
+    /// Non-success HTTP status, with whatever problem details the body carried.
+    Status {
+        status: StatusCode,
+        problem: api::Problem,
+    },
+    /// Success HTTP status, but the body carried `errors` and no usable `data`.
+    Errors {
+        status: StatusCode,
+        errors: Vec<api::Error>,
+    },
+    /// The body could not be decoded as either a response or problem details.
+    Decode {
+        status: StatusCode,
+        source: serde_json::Error,
+    },
+    /// Rejected locally, since the call would exceed [endpoint]'s budget until [reset].
+    RateLimitExceeded {
+        endpoint: String,
+        reset: DateTime<Utc>,
+    },
+}
+
+impl TwitterApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Status { status, .. }
+            | Self::Errors { status, .. }
+            | Self::Decode { status, .. } => *status,
+            Self::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        self.status() == StatusCode::UNAUTHORIZED
+    }
+
+    pub fn is_forbidden(&self) -> bool {
+        self.status() == StatusCode::FORBIDDEN
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::Status { status, .. } => *status == StatusCode::NOT_FOUND,
+            Self::Errors { errors, .. } => errors.iter().any(|error| {
+                error
+                    .r#type
+                    .as_deref()
+                    .is_some_and(|t| t.ends_with("resource-not-found"))
+            }),
+            Self::Decode { .. } | Self::RateLimitExceeded { .. } => false,
+        }
+    }
+
+    /// Interprets a response body, preferring typed failures over serde's "missing field" errors.
+    pub fn parse_response<T: DeserializeOwned>(status: StatusCode, body: &[u8]) -> Result<T, Self> {
+        if !status.is_success() {
+            let problem = serde_json::from_slice::<api::Problem>(body).unwrap_or_default();
+            return Err(Self::Status { status, problem });
+        }
+
+        serde_json::from_slice::<T>(body).map_err(|source| {
+            // NB: e.g. a lookup of a suspended user is a 200 with only `errors`
+            match serde_json::from_slice::<api::Problem>(body) {
+                Ok(api::Problem {
+                    errors: Some(errors),
+                    ..
+                }) if !errors.is_empty() => Self::Errors { status, errors },
+                _ => Self::Decode { status, source },
+            }
+        })
+    }
+}
+
+impl fmt::Display for TwitterApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { status, problem } => {
+                write!(f, "Twitter API error {status}")?;
+                if let Some(title) = &problem.title {
+                    write!(f, ": {title}")?;
+                }
+                if let Some(detail) = problem
+                    .detail
+                    .as_ref()
+                    .filter(|d| Some(*d) != problem.title.as_ref())
+                {
+                    write!(f, " ({detail})")?;
+                }
+                for error in problem.errors.iter().flatten() {
+                    write!(f, "; {}", error.description())?;
+                }
+                Ok(())
+            }
+            Self::Errors { errors, .. } => {
+                write!(f, "Twitter API returned errors")?;
+                for error in errors {
+                    write!(f, "; {}", error.description())?;
+                }
+                Ok(())
+            }
+            Self::Decode { status, source } => {
+                write!(
+                    f,
+                    "Failed to decode Twitter API response ({status}): {source}"
+                )
+            }
+            Self::RateLimitExceeded { endpoint, reset } => {
+                let reset = reset.with_timezone(&Local).format("%H:%M:%S");
+                write!(f, "Rate limit for {endpoint} exhausted until {reset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TwitterApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
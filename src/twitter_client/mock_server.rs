@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use hyper::body::Bytes;
+use hyper::header::HeaderMap;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use url::Url;
+
+/// A local stand-in for the Twitter v2 API, so that [Store] and the UI can be driven without
+/// network access.
+///
+/// Requests are answered from explicitly registered routes first, then from the fixture directory
+/// (if any), where `GET /2/users/me` is served from `<fixture_dir>/2/users/me.json`.  Query
+/// parameters are ignored by the fixture directory lookup.  A `.ndjson` fixture is served as a
+/// stream instead, see [MockResponse::stream].
+///
+/// [Store]: crate::store::Store
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: StatusCode,
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+    /// Send the body a line at a time, like the filtered stream does.
+    pub streamed: bool,
+    /// Keep a streamed body open after its last line, without sending anything more.
+    pub stalled: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+#[derive(Debug)]
+struct MockRoute {
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    responses: VecDeque<MockResponse>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    routes: Vec<MockRoute>,
+    fixture_dir: Option<PathBuf>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockResponse {
+    pub fn json(body: &str) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: Bytes::from(body.to_string()),
+            streamed: false,
+            stalled: false,
+        }
+    }
+
+    /// Newline-delimited JSON, sent a line at a time; the connection closes after the last line,
+    /// as if the stream had been disconnected.
+    pub fn stream(body: &str) -> Self {
+        Self {
+            streamed: true,
+            ..Self::json(body)
+        }
+    }
+
+    pub fn stream_fixture(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            streamed: true,
+            ..Self::fixture(path)?
+        })
+    }
+
+    pub fn fixture(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let body = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+        Ok(Self::json(&body))
+    }
+
+    /// The connection stalls after the body, or after a [MockResponse::stream]'s lines, rather
+    /// than closing.
+    pub fn stalled(mut self) -> Self {
+        self.stalled = true;
+        self
+    }
+
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn not_found(path: &str) -> Self {
+        let body = serde_json::json!({
+            "title": "Not Found Error",
+            "detail": format!("No mock route or fixture for {path}"),
+            "type": "about:blank",
+            "status": 404,
+        });
+        Self::json(&body.to_string()).with_status(StatusCode::NOT_FOUND)
+    }
+}
+
+impl MockRoute {
+    fn matches(&self, method: &Method, url: &Url) -> bool {
+        self.method == method
+            && self.path == url.path()
+            && self.query.iter().all(|(key, value)| {
+                url.query_pairs()
+                    .any(|(k, v)| k == key.as_str() && v == value.as_str())
+            })
+    }
+
+    /// Responses are served in order; the last one repeats forever.
+    fn next_response(&mut self) -> MockResponse {
+        if self.responses.len() > 1 {
+            self.responses.pop_front().unwrap()
+        } else {
+            self.responses.front().cloned().unwrap()
+        }
+    }
+}
+
+impl MockState {
+    fn respond(&mut self, method: &Method, url: &Url) -> MockResponse {
+        if let Some(route) = self
+            .routes
+            .iter_mut()
+            .find(|route| route.matches(method, url))
+        {
+            return route.next_response();
+        }
+
+        if let Some(fixture_dir) = &self.fixture_dir {
+            let relative_path = url.path().trim_start_matches('/');
+            let json_path = fixture_dir.join(format!("{relative_path}.json"));
+            if let Ok(response) = MockResponse::fixture(json_path) {
+                return response;
+            }
+            let ndjson_path = fixture_dir.join(format!("{relative_path}.ndjson"));
+            if let Ok(response) = MockResponse::stream_fixture(ndjson_path) {
+                return response;
+            }
+        }
+
+        MockResponse::not_found(url.path())
+    }
+}
+
+impl MockServer {
+    /// NB: must be called from within a tokio runtime; the server is spawned onto it.
+    pub fn start() -> Result<Self> {
+        Self::start_with_state(MockState::default())
+    }
+
+    pub fn start_with_fixture_dir(fixture_dir: impl AsRef<Path>) -> Result<Self> {
+        Self::start_with_state(MockState {
+            fixture_dir: Some(fixture_dir.as_ref().to_path_buf()),
+            ..MockState::default()
+        })
+    }
+
+    fn start_with_state(state: MockState) -> Result<Self> {
+        let state = Arc::new(Mutex::new(state));
+        let make_service = {
+            let state = state.clone();
+            make_service_fn(move |_conn| {
+                let state = state.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| handle_request(state.clone(), req)))
+                }
+            })
+        };
+
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))?.serve(make_service);
+        let addr = server.local_addr();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = server.with_graceful_shutdown(async {
+            shutdown_rx.await.ok();
+        });
+
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                eprintln!("Mock server error: {err}");
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown_tx),
+        })
+    }
+
+    /// Base URL to hand to [TwitterEndpoints::with_base_url].
+    ///
+    /// [TwitterEndpoints::with_base_url]: crate::twitter_client::TwitterEndpoints::with_base_url
+    pub fn base_url(&self) -> Url {
+        Url::parse(&format!("http://{}/", self.addr)).unwrap()
+    }
+
+    /// Registers [responses] for requests matching [method], [path] and every pair in [query]
+    /// (other query parameters are ignored).  Earlier routes take precedence.
+    pub fn route(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        responses: Vec<MockResponse>,
+    ) {
+        assert!(!responses.is_empty(), "Route needs at least one response");
+        let mut state = self.state.lock().unwrap();
+        state.routes.push(MockRoute {
+            method,
+            path: path.to_string(),
+            query: query
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            responses: responses.into(),
+        });
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+async fn handle_request(
+    state: Arc<Mutex<MockState>>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let url = Url::parse("http://localhost/")
+        .and_then(|base| base.join(&parts.uri.to_string()))
+        .unwrap();
+
+    let mock_response = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method: parts.method.clone(),
+            url: url.clone(),
+            headers: parts.headers.clone(),
+            body,
+        });
+        state.respond(&parts.method, &url)
+    };
+
+    let mut response = Response::builder().status(mock_response.status);
+    for (name, value) in &mock_response.headers {
+        response = response.header(name, value);
+    }
+    let body = if mock_response.stalled || mock_response.streamed {
+        let chunks = if mock_response.streamed {
+            mock_response
+                .body
+                .split_inclusive(|byte| *byte == b'\n')
+                .map(|line| Ok::<_, Infallible>(Bytes::copy_from_slice(line)))
+                .collect::<Vec<_>>()
+        } else {
+            vec![Ok(mock_response.body)]
+        };
+        let chunks = futures_util::stream::iter(chunks);
+        if mock_response.stalled {
+            Body::wrap_stream(chunks.chain(futures_util::stream::pending()))
+        } else {
+            Body::wrap_stream(chunks)
+        }
+    } else {
+        Body::from(mock_response.body)
+    };
+    Ok(response.body(body).unwrap())
+}
@@ -1,10 +1,20 @@
 pub mod api;
+mod error;
+pub mod mock_server;
+mod oauth_callback;
+mod rate_limit;
+mod retry;
+mod stream;
+
+pub use error::TwitterApiError;
+pub use rate_limit::{RateLimitBudget, RateLimits};
+pub use retry::RetryPolicy;
 
 use anyhow::{anyhow, Result};
-use hyper::body::Bytes;
+use chrono::SecondsFormat;
+use hyper::body::{Bytes, HttpBody};
 use hyper::client::HttpConnector;
-use hyper::server::conn::Http;
-use hyper::{Body, Client, Method, Request, Uri};
+use hyper::{Body, Client, Method, Request, StatusCode, Uri};
 use hyper_tls::HttpsConnector;
 use oauth2::basic::{BasicClient, BasicTokenResponse};
 use oauth2::reqwest::async_http_client;
@@ -12,22 +22,96 @@ use oauth2::{
     AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
     RedirectUrl, RefreshToken, Scope, TokenResponse, TokenUrl,
 };
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::net::SocketAddr;
-use std::{fs, process};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
+use tokio::sync::Mutex as AsyncMutex;
 use url::Url;
 
 pub type PagedResult<T> = Result<(T, Option<String>)>;
 
+/// NB: the longest query recent search accepts on the basic tiers (pro allows 1024).
+pub const MAX_SEARCH_QUERY_LEN: usize = 512;
+
+const MAX_WARNINGS: usize = 100;
+
+/// NB: the filtered stream sends a keep-alive every 20 seconds, so this long without anything
+/// means the connection has stalled.
+const STREAM_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Which token a request is sent with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Auth {
+    /// The signed-in user's access token, refreshed as needed.
+    User,
+    /// The app-only bearer token, see [TwitterClient::set_app_access_token].
+    App,
+}
+
 #[derive(Debug, Clone)]
 pub struct TwitterClient {
     https_client: Client<HttpsConnector<HttpConnector>>,
+    endpoints: TwitterEndpoints,
+    rate_limits: Arc<RateLimits>,
+    retry_policy: RetryPolicy,
+    stream_read_timeout: Duration,
     twitter_client_id: String,
     twitter_client_secret: String,
-    twitter_auth: TwitterAuth,
+    twitter_auth: Arc<Mutex<TwitterAuth>>,
+    twitter_auth_path: PathBuf,
+    // NB: refresh tokens are single-use, so concurrent refreshes must not race
+    twitter_auth_refresh_lock: Arc<AsyncMutex<()>>,
+    callback_port: u16,
+    // NB: the filtered stream only accepts app-only auth, not a user's access token
+    app_access_token: Option<AccessToken>,
+    // NB: `errors` that came alongside `data`, e.g. for a referenced tweet that was deleted
+    warnings: Arc<Mutex<Warnings>>,
+}
+
+/// Partial errors, i.e. `errors` that came alongside `data`, see [TwitterClient::warnings].
+#[derive(Debug, Clone, Default)]
+pub struct Warnings {
+    /// The most recent ones, oldest first, up to [MAX_WARNINGS].
+    pub recent: Vec<api::Error>,
+    /// How many there have been since they were last cleared, including those no longer kept.
+    pub count: usize,
+    /// Bumped on every change, so that whoever shows them can tell when to redraw.
+    pub generation: u64,
+}
+
+/// NB: [api_base_url] must end in a slash, since paths are joined onto it.
+#[derive(Debug, Clone)]
+pub struct TwitterEndpoints {
+    pub api_base_url: Url,
+    pub auth_url: Url,
+    pub token_url: Url,
+}
+
+impl Default for TwitterEndpoints {
+    fn default() -> Self {
+        Self {
+            api_base_url: Url::parse("https://api.twitter.com/2/").unwrap(),
+            auth_url: Url::parse("https://twitter.com/i/oauth2/authorize").unwrap(),
+            token_url: Url::parse("https://api.twitter.com/2/oauth2/token").unwrap(),
+        }
+    }
+}
+
+impl TwitterEndpoints {
+    /// Lays out every endpoint under [base_url] the same way as the real API, e.g. for pointing
+    /// at a [mock_server::MockServer].
+    pub fn with_base_url(base_url: &Url) -> Result<Self> {
+        Ok(Self {
+            api_base_url: base_url.join("2/")?,
+            auth_url: base_url.join("i/oauth2/authorize")?,
+            token_url: base_url.join("2/oauth2/token")?,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,240 +120,1464 @@ struct TwitterAuth {
     refresh_token: Option<RefreshToken>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DmIncludes {
+    #[serde(default)]
+    users: Vec<api::User>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TweetIncludes {
+    #[serde(default)]
+    users: Vec<api::User>,
+    #[serde(default)]
+    tweets: Vec<api::Tweet>,
+    #[serde(default)]
+    media: Vec<api::Media>,
+    #[serde(default)]
+    polls: Vec<api::Poll>,
+    #[serde(default)]
+    places: Vec<api::Place>,
+}
+
+/// [api::Response::data], for endpoints that always send it on success.  NB: e.g. a lookup of a
+/// suspended user is a 200 with only `errors`, which become the error here.
+fn expect_data<Data, Includes>(resp: api::Response<Data, Includes>) -> Result<Data> {
+    match (resp.data, resp.errors) {
+        (Some(data), _) => Ok(data),
+        (None, Some(errors)) if !errors.is_empty() => Err(TwitterApiError::Errors {
+            status: StatusCode::OK,
+            errors,
+        }
+        .into()),
+        (None, _) => Err(anyhow!("Expected `data`")),
+    }
+}
+
+fn append_tweet_fields(uri: &mut Url) {
+    uri.query_pairs_mut()
+        .append_pair(
+            "tweet.fields",
+            "created_at,attachments,referenced_tweets,public_metrics,conversation_id,entities,note_tweet,geo",
+        )
+        .append_pair("user.fields", api::USER_FIELDS)
+        .append_pair(
+            "media.fields",
+            "type,url,preview_image_url,width,height,alt_text,duration_ms",
+        )
+        .append_pair(
+            "poll.fields",
+            "options,duration_minutes,end_datetime,voting_status",
+        )
+        .append_pair(
+            "place.fields",
+            "full_name,name,country,country_code,place_type",
+        )
+        .append_pair(
+            "expansions",
+            "author_id,attachments.media_keys,attachments.poll_ids,geo.place_id,\
+             referenced_tweets.id,referenced_tweets.id.author_id",
+        );
+}
+
+/// Fills in what each tweet refers to (author, media, poll, place and referenced tweets) from
+/// the response's `includes`.
+fn resolve_includes(tweets: Vec<api::Tweet>, includes: &TweetIncludes) -> Vec<api::Tweet> {
+    let included_tweets: HashMap<&str, &api::Tweet> = includes
+        .tweets
+        .iter()
+        .map(|tweet| (tweet.id.as_str(), tweet))
+        .collect();
+    tweets
+        .into_iter()
+        .map(|tweet| {
+            let mut tweet = resolve_tweet_includes(tweet, includes);
+            let referenced_tweets = tweet
+                .referenced_tweets
+                .iter()
+                .flatten()
+                .filter_map(|reference| included_tweets.get(reference.id.as_str()))
+                .map(|included| resolve_tweet_includes((*included).clone(), includes))
+                .collect::<Vec<api::Tweet>>();
+            if !referenced_tweets.is_empty() {
+                tweet.included_tweets = Some(referenced_tweets);
+            }
+            tweet
+        })
+        .collect()
+}
+
+fn resolve_tweet_includes(tweet: api::Tweet, includes: &TweetIncludes) -> api::Tweet {
+    let author = includes
+        .users
+        .iter()
+        .find(|user| user.id == tweet.author_id);
+    let attachments = tweet.attachments.as_ref();
+    let media_keys = attachments.and_then(|a| a.media_keys.as_ref());
+    let media = media_keys.map(|media_keys| {
+        media_keys
+            .iter()
+            .filter_map(|media_key| {
+                includes
+                    .media
+                    .iter()
+                    .find(|media| &media.media_key == media_key)
+                    .cloned()
+            })
+            .collect()
+    });
+    let poll_ids = attachments.and_then(|a| a.poll_ids.as_ref());
+    let poll = poll_ids
+        .and_then(|poll_ids| poll_ids.first())
+        .and_then(|poll_id| includes.polls.iter().find(|poll| &poll.id == poll_id))
+        .cloned();
+    let place_id = tweet.geo.as_ref().and_then(|geo| geo.place_id.as_ref());
+    let place = place_id
+        .and_then(|place_id| includes.places.iter().find(|place| &place.id == place_id))
+        .cloned();
+
+    api::Tweet {
+        author: author.cloned(),
+        media,
+        poll,
+        place,
+        ..tweet
+    }
+}
+
 impl TwitterClient {
     pub fn new(twitter_client_id: &str, twitter_client_secret: &str) -> Self {
+        Self::new_with_endpoints(
+            twitter_client_id,
+            twitter_client_secret,
+            TwitterEndpoints::default(),
+        )
+    }
+
+    pub fn new_with_endpoints(
+        twitter_client_id: &str,
+        twitter_client_secret: &str,
+        endpoints: TwitterEndpoints,
+    ) -> Self {
         let https = HttpsConnector::new();
         let https_client = Client::builder().build::<_, hyper::Body>(https);
         Self {
             https_client,
+            endpoints,
+            rate_limits: Arc::new(RateLimits::default()),
+            retry_policy: RetryPolicy::default(),
+            stream_read_timeout: STREAM_READ_TIMEOUT,
             twitter_client_id: twitter_client_id.to_string(),
             twitter_client_secret: twitter_client_secret.to_string(),
-            twitter_auth: TwitterAuth {
+            twitter_auth: Arc::new(Mutex::new(TwitterAuth {
                 access_token: None,
                 refresh_token: None,
-            },
+            })),
+            twitter_auth_path: PathBuf::from("./var/.oauth"),
+            twitter_auth_refresh_lock: Arc::new(AsyncMutex::new(())),
+            callback_port: 8080,
+            app_access_token: None,
+            warnings: Arc::new(Mutex::new(Warnings::default())),
         }
     }
 
+    /// The most recent partial errors, oldest first.
+    pub fn warnings(&self) -> Warnings {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    pub fn warnings_generation(&self) -> u64 {
+        self.warnings.lock().unwrap().generation
+    }
+
+    /// NB: called as a feed restarts, so that only what's relevant to it is shown.
+    pub fn clear_warnings(&self) {
+        let mut warnings = self.warnings.lock().unwrap();
+        if warnings.count > 0 {
+            warnings.recent.clear();
+            warnings.count = 0;
+            warnings.generation += 1;
+        }
+    }
+
+    pub fn add_warning(&self, warning: api::Error) {
+        self.add_warnings(Some(vec![warning]));
+    }
+
+    fn add_warnings(&self, errors: Option<Vec<api::Error>>) {
+        let errors = errors.unwrap_or_default();
+        if errors.is_empty() {
+            return;
+        }
+        let mut warnings = self.warnings.lock().unwrap();
+        warnings.count += errors.len();
+        warnings.generation += 1;
+        warnings.recent.extend(errors);
+        let excess = warnings.recent.len().saturating_sub(MAX_WARNINGS);
+        warnings.recent.drain(..excess);
+    }
+
+    pub fn set_auth_path(&mut self, path: &Path) {
+        self.twitter_auth_path = path.to_path_buf();
+    }
+
     pub fn save_auth(&self) -> Result<()> {
-        let str = serde_json::to_string(&self.twitter_auth)?;
-        fs::write("./var/.oauth", str)?;
+        let str = serde_json::to_string(&*self.twitter_auth.lock().unwrap())?;
+        fs::write(&self.twitter_auth_path, str)?;
         Ok(())
     }
 
     pub fn load_auth(&mut self) -> Result<()> {
-        let str = fs::read_to_string("./var/.oauth")?;
-        self.twitter_auth = serde_json::from_str(&str)?;
+        let str = fs::read_to_string(&self.twitter_auth_path)?;
+        *self.twitter_auth.lock().unwrap() = serde_json::from_str(&str)?;
         Ok(())
     }
 
-    pub async fn authorize(&mut self, use_refresh_token: bool) -> Result<()> {
-        let oauth_client = BasicClient::new(
+    /// NB: bypasses the OAuth flow entirely; only useful against a mock server.
+    pub fn set_access_token(&mut self, access_token: &str) {
+        let mut twitter_auth = self.twitter_auth.lock().unwrap();
+        twitter_auth.access_token = Some(AccessToken::new(access_token.to_string()));
+    }
+
+    pub fn set_refresh_token(&mut self, refresh_token: &str) {
+        let mut twitter_auth = self.twitter_auth.lock().unwrap();
+        twitter_auth.refresh_token = Some(RefreshToken::new(refresh_token.to_string()));
+    }
+
+    fn set_token(&self, token: &BasicTokenResponse) {
+        let mut twitter_auth = self.twitter_auth.lock().unwrap();
+        twitter_auth.access_token = Some(token.access_token().clone());
+        // NB: keep the old refresh token if the server didn't rotate it
+        if let Some(refresh_token) = token.refresh_token() {
+            twitter_auth.refresh_token = Some(refresh_token.clone());
+        }
+    }
+
+    fn oauth_client(&self) -> Result<BasicClient> {
+        Ok(BasicClient::new(
             ClientId::new(self.twitter_client_id.clone()),
             Some(ClientSecret::new(self.twitter_client_secret.clone())),
-            AuthUrl::new("https://twitter.com/i/oauth2/authorize".to_string())?,
-            Some(TokenUrl::new(
-                "https://api.twitter.com/2/oauth2/token".to_string(),
-            )?),
+            AuthUrl::from_url(self.endpoints.auth_url.clone()),
+            Some(TokenUrl::from_url(self.endpoints.token_url.clone())),
         )
-        .set_redirect_uri(RedirectUrl::new("https://localhost:8080".to_string())?);
+        .set_redirect_uri(RedirectUrl::new(format!(
+            "http://127.0.0.1:{}",
+            self.callback_port
+        ))?))
+    }
+
+    /// Returns a client sharing all state with this one, except that its calls are retried
+    /// according to [retry_policy].
+    pub fn with_retry_policy(&self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self.clone()
+        }
+    }
+
+    /// Like [with_retry_policy], but for how long the filtered stream may go without data.
+    pub fn with_stream_read_timeout(&self, stream_read_timeout: Duration) -> Self {
+        Self {
+            stream_read_timeout,
+            ..self.clone()
+        }
+    }
+
+    pub fn rate_limits(&self) -> &RateLimits {
+        &self.rate_limits
+    }
+
+    fn api_url(&self, path: &str) -> Result<Url> {
+        Ok(self.endpoints.api_base_url.join(path)?)
+    }
+
+    /// NB: the redirect URL registered with the Twitter app must be `http://127.0.0.1:<port>`.
+    pub fn set_callback_port(&mut self, callback_port: u16) {
+        self.callback_port = callback_port;
+    }
+
+    /// Bearer token for the endpoints that require app-only auth, i.e. the filtered stream and
+    /// its rules, and tweet counts.  Without one, the user's access token is tried.
+    pub fn set_app_access_token(&mut self, app_access_token: &str) {
+        self.app_access_token = Some(AccessToken::new(app_access_token.to_string()));
+    }
+
+    pub fn has_app_access_token(&self) -> bool {
+        self.app_access_token.is_some()
+    }
+
+    pub async fn authorize(&mut self, use_refresh_token: bool) -> Result<()> {
+        let oauth_client = self.oauth_client()?;
         let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
-        let (auth_url, _csrf_token) = oauth_client
+        let (auth_url, csrf_token) = oauth_client
             .authorize_url(CsrfToken::new_random)
             .add_scope(Scope::new("tweet.read".to_string()))
+            .add_scope(Scope::new("tweet.write".to_string()))
             .add_scope(Scope::new("users.read".to_string()))
+            .add_scope(Scope::new("like.write".to_string()))
+            .add_scope(Scope::new("bookmark.read".to_string()))
+            .add_scope(Scope::new("bookmark.write".to_string()))
+            .add_scope(Scope::new("list.read".to_string()))
+            .add_scope(Scope::new("follows.read".to_string()))
+            .add_scope(Scope::new("follows.write".to_string()))
+            .add_scope(Scope::new("dm.read".to_string()))
+            .add_scope(Scope::new("dm.write".to_string()))
+            .add_scope(Scope::new("mute.read".to_string()))
+            .add_scope(Scope::new("mute.write".to_string()))
+            .add_scope(Scope::new("block.read".to_string()))
             .add_scope(Scope::new("offline.access".to_string()))
             .set_pkce_challenge(pkce_challenge)
             .url();
 
-        match &self.twitter_auth.refresh_token {
+        let refresh_token = self.twitter_auth.lock().unwrap().refresh_token.clone();
+        match refresh_token {
             Some(refresh_token) if use_refresh_token => {
                 let token = oauth_client
-                    .exchange_refresh_token(refresh_token)
+                    .exchange_refresh_token(&refresh_token)
                     .request_async(async_http_client)
                     .await?;
-                self.twitter_auth.access_token = Some(token.access_token().clone());
-                self.twitter_auth.refresh_token = token.refresh_token().cloned();
+                self.set_token(&token);
                 self.save_auth()?;
             }
             _ => {
-                // User browses here to complete OAuth flow
-                process::Command::new("open")
-                    .arg(auth_url.to_string())
-                    .output()
-                    .expect(&format!("Failed to open url in browser: {auth_url}"));
-
-                let mut callback_url = String::new();
-                println!("Enter callback url:");
-                std::io::stdin().read_line(&mut callback_url)?;
-                let callback_url = Url::parse(&callback_url)?;
-
-                // let (set_authorization_code, mut authorization_code) =
-                //     tokio::sync::mpsc::channel::<String>(1);
-                // let callback_addr = SocketAddr::from(([127, 0, 0, 1], 8080));
-                // let callback_listener = TcpListener::bind(callback_addr).await?;
-
-                fn parse_authorization_code(url: &Url) -> Result<String> {
-                    let mut expected_csrf_state = None;
-                    let mut authorization_code = None;
-                    for (key, value) in url.query_pairs() {
-                        if key == "state" {
-                            expected_csrf_state = Some(String::from(value));
-                        } else if key == "code" {
-                            authorization_code = Some(String::from(value));
-                        }
+                let authorization_code = match TcpListener::bind(("127.0.0.1", self.callback_port))
+                    .await
+                {
+                    Ok(callback_listener) => {
+                        oauth_callback::open_in_browser(&auth_url);
+                        oauth_callback::receive_authorization_code(callback_listener, &csrf_token)
+                            .await?
                     }
-                    let _expected_csrf_state = expected_csrf_state
-                        .ok_or(anyhow!("Missing `state` param from callback"))?;
-                    let authorization_code =
-                        authorization_code.ok_or(anyhow!("Missing `code` param from callback"))?;
-
-                    // Once the user has been redirected to the redirect URL, you'll have access to the
-                    // authorization code. For security reasons, your code should verify that the `state`
-                    // parameter returned by the server matches `csrf_state`.
-                    Ok(authorization_code)
-                }
+                    Err(err) => {
+                        // NB: fall back to having the user copy the redirect out of the browser
+                        eprintln!("Cannot listen on port {}: {err}", self.callback_port);
+                        oauth_callback::open_in_browser(&auth_url);
+
+                        let mut callback_url = String::new();
+                        println!("Enter callback url:");
+                        std::io::stdin().read_line(&mut callback_url)?;
+                        let callback_url = Url::parse(callback_url.trim())?;
+                        oauth_callback::parse_authorization_code(&callback_url, &csrf_token)?
+                    }
+                };
 
-                // tokio::task::spawn(async move {
-                //     loop {
-                //         let (stream, _) = callback_listener.accept().await.unwrap();
-                //         if let Err(err) = Http::new()
-                //             .serve_connection(
-                //                 stream,
-                //                 hyper::service::service_fn(|req| {
-                //                     let set_authorization_code = set_authorization_code.clone();
-                //                     async move {
-                //                         let authorization_code = parse_authorization_code(req.uri())?;
-                //                         set_authorization_code.send(authorization_code).await?;
-                //                         Ok::<_, anyhow::Error>(hyper::Response::new(hyper::Body::from(
-                //                             "You can close this window now",
-                //                         )))
-                //                     }
-                //                 }),
-                //             )
-                //             .await
-                //         {
-                //             eprintln!("Error serving callback: {}", err);
-                //         }
-                //     }
-                // });
-
-                let authorization_code = parse_authorization_code(&callback_url)?;
                 let token_result = oauth_client
                     .exchange_code(AuthorizationCode::new(authorization_code))
                     .set_pkce_verifier(pkce_verifier)
                     .request_async(async_http_client)
                     .await?;
 
-                self.twitter_auth.access_token = Some(token_result.access_token().clone());
-                self.twitter_auth.refresh_token = token_result.refresh_token().cloned();
+                self.set_token(&token_result);
             }
         }
         Ok(())
     }
 
-    async fn authenticated_get(&self, uri: &Url) -> Result<Bytes> {
-        let access_token = self
-            .twitter_auth
+    fn access_token(&self) -> Result<AccessToken> {
+        let twitter_auth = self.twitter_auth.lock().unwrap();
+        twitter_auth
             .access_token
-            .as_ref()
-            .ok_or(anyhow!("Unauthorized"))?;
+            .clone()
+            .ok_or(anyhow!("Unauthorized"))
+    }
+
+    fn has_refresh_token(&self) -> bool {
+        self.twitter_auth.lock().unwrap().refresh_token.is_some()
+    }
+
+    /// Exchanges the refresh token for a new access token, unless [expired_access_token] has
+    /// already been replaced by a concurrent refresh.
+    async fn refresh_access_token(&self, expired_access_token: &AccessToken) -> Result<()> {
+        let _refresh_guard = self.twitter_auth_refresh_lock.lock().await;
+
+        let refresh_token = {
+            let twitter_auth = self.twitter_auth.lock().unwrap();
+            let access_token = twitter_auth.access_token.as_ref().map(|t| t.secret());
+            if access_token != Some(expired_access_token.secret()) {
+                return Ok(());
+            }
+            twitter_auth
+                .refresh_token
+                .clone()
+                .ok_or(anyhow!("No refresh token"))?
+        };
+
+        let token = self
+            .oauth_client()?
+            .exchange_refresh_token(&refresh_token)
+            .request_async(async_http_client)
+            .await?;
+        self.set_token(&token);
+        self.save_auth()
+    }
+
+    async fn send(
+        &self,
+        method: &Method,
+        uri: &Url,
+        body: &Option<Bytes>,
+        access_token: &AccessToken,
+    ) -> Result<hyper::Response<Body>> {
         let req = Request::builder()
-            .method(Method::GET)
+            .method(method)
             .uri(uri.to_string())
-            .header("Authorization", format!("Bearer {}", access_token.secret()))
-            .body(Body::empty())?;
-        let resp = self.https_client.request(req).await?;
-        let resp = hyper::body::to_bytes(resp.into_body()).await?;
+            .header("Authorization", format!("Bearer {}", access_token.secret()));
+        let req = match body {
+            Some(body) => req
+                .header("Content-Type", "application/json")
+                .body(Body::from(body.clone()))?,
+            None => req.body(Body::empty())?,
+        };
+        Ok(self.https_client.request(req).await?)
+    }
+
+    /// NB: [endpoint] is the path template used to track the rate limit, e.g. `users/:id/tweets`.
+    async fn authenticated_request(
+        &self,
+        auth: Auth,
+        method: Method,
+        endpoint: &'static str,
+        uri: &Url,
+        body: Option<serde_json::Value>,
+    ) -> Result<hyper::Response<Bytes>> {
+        // NB: a POST that timed out may well have gone through, so don't risk e.g. double tweeting
+        let retry_policy = if method == Method::POST {
+            RetryPolicy::never()
+        } else {
+            self.retry_policy
+        };
+        let body = body.map(|body| Bytes::from(body.to_string()));
+
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .authenticated_request_once(auth, &method, endpoint, uri, &body)
+                .await;
+            match retry_policy.retry_delay(attempt, &result) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn authenticated_request_once(
+        &self,
+        auth: Auth,
+        method: &Method,
+        endpoint: &'static str,
+        uri: &Url,
+        body: &Option<Bytes>,
+    ) -> Result<hyper::Response<Bytes>> {
+        let resp = self
+            .authenticated_send(auth, method, endpoint, uri, body)
+            .await?;
+        let (parts, body) = resp.into_parts();
+        let bytes = hyper::body::to_bytes(body).await?;
+        Ok(hyper::Response::from_parts(parts, bytes))
+    }
+
+    /// Like [authenticated_request_once], but leaves the body to be read by the caller.
+    async fn authenticated_send(
+        &self,
+        auth: Auth,
+        method: &Method,
+        endpoint: &'static str,
+        uri: &Url,
+        body: &Option<Bytes>,
+    ) -> Result<hyper::Response<Body>> {
+        self.rate_limits.acquire(endpoint).await?;
+
+        // NB: without an app token, the user's is tried and the API can say no
+        let app_access_token = self.app_access_token.as_ref().filter(|_| auth == Auth::App);
+        let resp = if let Some(app_access_token) = app_access_token {
+            self.send(method, uri, body, app_access_token).await?
+        } else {
+            let access_token = self.access_token()?;
+            let mut resp = self.send(method, uri, body, &access_token).await?;
+            if resp.status() == StatusCode::UNAUTHORIZED && self.has_refresh_token() {
+                // NB: the access token expires after two hours; retry once with a fresh one
+                self.refresh_access_token(&access_token).await?;
+                resp = self.send(method, uri, body, &self.access_token()?).await?;
+            }
+            resp
+        };
+
+        self.rate_limits.update(endpoint, resp.headers());
         Ok(resp)
     }
 
+    async fn request_response_as<T: DeserializeOwned>(
+        &self,
+        auth: Auth,
+        method: Method,
+        endpoint: &'static str,
+        uri: &Url,
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        let resp = self
+            .authenticated_request(auth, method, endpoint, uri, body)
+            .await?;
+        Ok(TwitterApiError::parse_response(resp.status(), resp.body())?)
+    }
+
+    async fn request_response<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: &'static str,
+        uri: &Url,
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        self.request_response_as(Auth::User, method, endpoint, uri, body)
+            .await
+    }
+
+    async fn get_response<T: DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        uri: &Url,
+    ) -> Result<T> {
+        self.request_response(Method::GET, endpoint, uri, None)
+            .await
+    }
+
     pub async fn me(&self) -> Result<api::User> {
-        let uri = Url::parse("https://api.twitter.com/2/users/me")?;
-        let bytes = self.authenticated_get(&uri).await?;
-        let resp: api::Response<api::User, ()> = serde_json::from_slice(&bytes)?;
-        Ok(resp.data)
+        let mut uri = self.api_url("users/me")?;
+        uri.query_pairs_mut()
+            .append_pair("user.fields", api::USER_FIELDS);
+        let resp: api::Response<api::User, ()> = self.get_response("users/me", &uri).await?;
+        expect_data(resp)
     }
 
     pub async fn user_by_username(&self, username: &str) -> Result<api::User> {
-        let mut uri = Url::parse(&format!(
-            "https://api.twitter.com/2/users/by/username/{username}"
-        ))?;
-        uri.query_pairs_mut().append_pair("user.fields", "username");
-        let bytes = self.authenticated_get(&uri).await?;
-        let resp: api::Response<api::User, ()> = serde_json::from_slice(&bytes)?;
-        Ok(resp.data)
+        let mut uri = self.api_url(&format!("users/by/username/{username}"))?;
+        uri.query_pairs_mut()
+            .append_pair("user.fields", api::USER_FIELDS);
+        let resp: api::Response<api::User, ()> = self
+            .get_response("users/by/username/:username", &uri)
+            .await?;
+        expect_data(resp)
+    }
+
+    /// Looks up [usernames] in batches of 100; like [TwitterClient::tweets_by_ids], users that
+    /// can't be found are left out.
+    pub async fn users_by_usernames(&self, usernames: &[String]) -> Result<Vec<api::User>> {
+        self.users_by("users/by", "usernames", usernames).await
+    }
+
+    pub async fn users_by_ids(&self, user_ids: &[String]) -> Result<Vec<api::User>> {
+        self.users_by("users", "ids", user_ids).await
+    }
+
+    async fn users_by(
+        &self,
+        endpoint: &'static str,
+        key: &str,
+        values: &[String],
+    ) -> Result<Vec<api::User>> {
+        let mut users = Vec::new();
+        for batch in values.chunks(100) {
+            let mut uri = self.api_url(endpoint)?;
+            uri.query_pairs_mut()
+                .append_pair(key, &batch.join(","))
+                .append_pair("user.fields", api::USER_FIELDS);
+
+            let resp: api::Response<Vec<api::User>, ()> = self.get_response(endpoint, &uri).await?;
+            self.add_warnings(resp.errors);
+            users.append(&mut resp.data.unwrap_or_default());
+        }
+        Ok(users)
     }
 
     async fn get_tweets_with_users(
         &self,
+        endpoint: &'static str,
         uri: &mut Url,
         pagination_token: Option<String>,
     ) -> PagedResult<Vec<api::Tweet>> {
+        append_tweet_fields(uri);
+        uri.query_pairs_mut().append_pair("max_results", "100");
+        if let Some(pagination_token) = pagination_token {
+            uri.query_pairs_mut()
+                .append_pair("pagination_token", &pagination_token);
+        }
+
+        let resp: api::Response<Vec<api::Tweet>, TweetIncludes> =
+            self.get_response(endpoint, uri).await?;
+        self.add_warnings(resp.errors);
+        let next_pagination_token = resp.meta.and_then(|meta| meta.next_token);
+        let includes = resp.includes.unwrap_or_default();
+        Ok((
+            resolve_includes(resp.data.unwrap_or_default(), &includes),
+            next_pagination_token,
+        ))
+    }
+
+    /// Looks up [tweet_ids] in batches of 100 (the most `GET /2/tweets` accepts).  Tweets that
+    /// are deleted or not visible to us are left out rather than failing the batch.
+    pub async fn tweets_by_ids(&self, tweet_ids: &[String]) -> Result<Vec<api::Tweet>> {
+        let mut tweets = Vec::new();
+        for batch in tweet_ids.chunks(100) {
+            let mut uri = self.api_url("tweets")?;
+            uri.query_pairs_mut().append_pair("ids", &batch.join(","));
+            append_tweet_fields(&mut uri);
+
+            let resp: api::Response<Vec<api::Tweet>, TweetIncludes> =
+                self.get_response("tweets", &uri).await?;
+            self.add_warnings(resp.errors);
+            let includes = resp.includes.unwrap_or_default();
+            tweets.append(&mut resolve_includes(
+                resp.data.unwrap_or_default(),
+                &includes,
+            ));
+        }
+        Ok(tweets)
+    }
+
+    pub async fn user_tweets(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::Tweet>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/tweets"))?;
+        self.get_tweets_with_users("users/:id/tweets", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn timeline_reverse_chronological(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::Tweet>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/timelines/reverse_chronological"))?;
+        let endpoint = "users/:id/timelines/reverse_chronological";
+        self.get_tweets_with_users(endpoint, &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn user_mentions(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::Tweet>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/mentions"))?;
+        self.get_tweets_with_users("users/:id/mentions", &mut uri, pagination_token)
+            .await
+    }
+
+    /// NB: search pages with `next_token` rather than `pagination_token`.
+    pub async fn search_tweets(
+        &self,
+        query: &str,
+        window: &api::SearchWindow,
+        next_token: Option<String>,
+    ) -> PagedResult<Vec<api::Tweet>> {
+        let mut uri = self.api_url("tweets/search/recent")?;
+        {
+            let mut query_pairs = uri.query_pairs_mut();
+            query_pairs.append_pair("query", query);
+            if let Some(since_id) = &window.since_id {
+                query_pairs.append_pair("since_id", since_id);
+            }
+            if let Some(until_id) = &window.until_id {
+                query_pairs.append_pair("until_id", until_id);
+            }
+            if let Some(start_time) = window.start_time {
+                query_pairs.append_pair(
+                    "start_time",
+                    &start_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+                );
+            }
+            if let Some(end_time) = window.end_time {
+                query_pairs.append_pair(
+                    "end_time",
+                    &end_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+                );
+            }
+            if let Some(next_token) = next_token {
+                query_pairs.append_pair("next_token", &next_token);
+            }
+        }
+        self.get_tweets_with_users("tweets/search/recent", &mut uri, None)
+            .await
+    }
+
+    /// Counts the tweets matching [query] over the last seven days, oldest bucket first.
+    pub async fn tweet_counts(
+        &self,
+        query: &str,
+        granularity: api::Granularity,
+    ) -> Result<Vec<api::TweetCount>> {
+        let mut uri = self.api_url("tweets/counts/recent")?;
         uri.query_pairs_mut()
-            .append_pair(
-                "tweet.fields",
-                "created_at,attachments,referenced_tweets,public_metrics,conversation_id",
-            )
-            .append_pair("user.fields", "username")
-            .append_pair("expansions", "author_id")
+            .append_pair("query", query)
+            .append_pair("granularity", granularity.as_str());
+        let resp: api::Response<Vec<api::TweetCount>, ()> = self
+            .request_response_as(Auth::App, Method::GET, "tweets/counts/recent", &uri, None)
+            .await?;
+        self.add_warnings(resp.errors);
+        Ok(resp.data.unwrap_or_default())
+    }
+
+    /// NB: the response only carries the new tweet's id and text.
+    pub async fn create_tweet(&self, new_tweet: &api::NewTweet) -> Result<api::CreatedTweet> {
+        let uri = self.api_url("tweets")?;
+        let body = serde_json::to_value(new_tweet)?;
+        let resp: api::Response<api::CreatedTweet, ()> = self
+            .request_response(Method::POST, "POST tweets", &uri, Some(body))
+            .await?;
+        expect_data(resp)
+    }
+
+    pub async fn like_tweet(&self, user_id: &str, tweet_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/likes"))?;
+        let body = serde_json::json!({ "tweet_id": tweet_id });
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::POST, "POST users/:id/likes", &uri, Some(body))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unlike_tweet(&self, user_id: &str, tweet_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/likes/{tweet_id}"))?;
+        let endpoint = "DELETE users/:id/likes/:tweet_id";
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::DELETE, endpoint, &uri, None)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn retweet(&self, user_id: &str, tweet_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/retweets"))?;
+        let body = serde_json::json!({ "tweet_id": tweet_id });
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::POST, "POST users/:id/retweets", &uri, Some(body))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unretweet(&self, user_id: &str, tweet_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/retweets/{tweet_id}"))?;
+        let endpoint = "DELETE users/:id/retweets/:tweet_id";
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::DELETE, endpoint, &uri, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_lists(
+        &self,
+        endpoint: &'static str,
+        uri: &mut Url,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::List>> {
+        uri.query_pairs_mut()
+            .append_pair("list.fields", "description,private,owner_id")
             .append_pair("max_results", "100");
         if let Some(pagination_token) = pagination_token {
             uri.query_pairs_mut()
                 .append_pair("pagination_token", &pagination_token);
         }
-        let bytes = self.authenticated_get(&uri).await?;
 
-        #[derive(Debug, Serialize, Deserialize)]
-        struct Includes {
-            users: Vec<api::User>,
+        let resp: api::Response<Vec<api::List>, ()> = self.get_response(endpoint, uri).await?;
+        self.add_warnings(resp.errors);
+        let next_pagination_token = resp.meta.and_then(|meta| meta.next_token);
+        Ok((resp.data.unwrap_or_default(), next_pagination_token))
+    }
+
+    pub async fn owned_lists(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::List>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/owned_lists"))?;
+        self.get_lists("users/:id/owned_lists", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn followed_lists(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::List>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/followed_lists"))?;
+        self.get_lists("users/:id/followed_lists", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn list_tweets(
+        &self,
+        list_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::Tweet>> {
+        let mut uri = self.api_url(&format!("lists/{list_id}/tweets"))?;
+        self.get_tweets_with_users("lists/:id/tweets", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn stream_rules(&self) -> Result<Vec<api::StreamRule>> {
+        let uri = self.api_url("tweets/search/stream/rules")?;
+        let endpoint = "tweets/search/stream/rules";
+        let resp: api::Response<Vec<api::StreamRule>, ()> = self
+            .request_response_as(Auth::App, Method::GET, endpoint, &uri, None)
+            .await?;
+        Ok(resp.data.unwrap_or_default())
+    }
+
+    pub async fn add_stream_rules(
+        &self,
+        rules: &[api::NewStreamRule],
+    ) -> Result<Vec<api::StreamRule>> {
+        let uri = self.api_url("tweets/search/stream/rules")?;
+        let body = serde_json::json!({ "add": rules });
+        let endpoint = "POST tweets/search/stream/rules";
+        let resp: api::Response<Vec<api::StreamRule>, ()> = self
+            .request_response_as(Auth::App, Method::POST, endpoint, &uri, Some(body))
+            .await?;
+        Ok(resp.data.unwrap_or_default())
+    }
+
+    pub async fn delete_stream_rules(&self, rule_ids: &[String]) -> Result<()> {
+        let uri = self.api_url("tweets/search/stream/rules")?;
+        let body = serde_json::json!({ "delete": { "ids": rule_ids } });
+        let endpoint = "POST tweets/search/stream/rules";
+        let _: serde_json::Value = self
+            .request_response_as(Auth::App, Method::POST, endpoint, &uri, Some(body))
+            .await?;
+        Ok(())
+    }
+
+    /// Follows the filtered stream, handing each tweet matching the stream rules to [on_tweet].
+    /// Reconnects with backoff when disconnected, per the client's [RetryPolicy], where a
+    /// connection that delivered at least one line resets the attempt count.  Only returns once
+    /// reconnecting is given up on.
+    pub async fn filtered_stream(&self, mut on_tweet: impl FnMut(api::Tweet)) -> Result<()> {
+        let mut uri = self.api_url("tweets/search/stream")?;
+        append_tweet_fields(&mut uri);
+
+        let mut attempt = 1;
+        loop {
+            // NB: not just connecting, or a server that accepts and then hangs up at once would
+            // be reconnected to forever
+            let mut received_line = false;
+            let result = self
+                .follow_stream_once(&uri, &mut on_tweet, &mut received_line)
+                .await;
+            if received_line {
+                attempt = 1;
+            }
+
+            match self.retry_policy.retry_delay(attempt, &result) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => {
+                    let resp = result?;
+                    let status = resp.status();
+                    TwitterApiError::parse_response::<serde_json::Value>(status, resp.body())?;
+                    return Err(anyhow!("Filtered stream ended ({status})"));
+                }
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Returns the response if the stream couldn't be connected, or an error once it drops.
+    async fn follow_stream_once(
+        &self,
+        uri: &Url,
+        on_tweet: &mut impl FnMut(api::Tweet),
+        received_line: &mut bool,
+    ) -> Result<hyper::Response<Bytes>> {
+        let resp = self
+            .authenticated_send(Auth::App, &Method::GET, "tweets/search/stream", uri, &None)
+            .await?;
+        if !resp.status().is_success() {
+            let (parts, body) = resp.into_parts();
+            let bytes = hyper::body::to_bytes(body).await?;
+            return Ok(hyper::Response::from_parts(parts, bytes));
+        }
+
+        let mut body = resp.into_body();
+        let mut line_buffer = stream::LineBuffer::default();
+        loop {
+            let chunk = match tokio::time::timeout(self.stream_read_timeout, body.data()).await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "Filtered stream stalled",
+                    )
+                    .into())
+                }
+            };
+            for line in line_buffer.push(&chunk?) {
+                *received_line = true;
+                // NB: one bad line shouldn't end the stream; it's kept as a warning and skipped
+                match stream::decode_line(&line) {
+                    Ok(Some(tweet)) => on_tweet(tweet),
+                    Ok(None) => (),
+                    Err(error) => self.add_warning(api::Error::new(
+                        "Undecodable stream line",
+                        &error.to_string(),
+                    )),
+                }
+            }
+        }
+
+        // NB: io errors are retried, so that a clean disconnect is reconnected too
+        Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "Filtered stream disconnected",
+        )
+        .into())
+    }
+
+    pub async fn bookmarks(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::Tweet>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/bookmarks"))?;
+        self.get_tweets_with_users("users/:id/bookmarks", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn bookmark_tweet(&self, user_id: &str, tweet_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/bookmarks"))?;
+        let body = serde_json::json!({ "tweet_id": tweet_id });
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::POST, "POST users/:id/bookmarks", &uri, Some(body))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unbookmark_tweet(&self, user_id: &str, tweet_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/bookmarks/{tweet_id}"))?;
+        let endpoint = "DELETE users/:id/bookmarks/:tweet_id";
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::DELETE, endpoint, &uri, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_dm_events(
+        &self,
+        endpoint: &'static str,
+        uri: &mut Url,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::DmEvent>> {
+        uri.query_pairs_mut()
+            .append_pair(
+                "dm_event.fields",
+                "id,text,event_type,created_at,sender_id,dm_conversation_id,participant_ids",
+            )
+            .append_pair("expansions", "sender_id,participant_ids")
+            .append_pair("user.fields", api::USER_FIELDS)
+            .append_pair("max_results", "100");
+        if let Some(pagination_token) = pagination_token {
+            uri.query_pairs_mut()
+                .append_pair("pagination_token", &pagination_token);
         }
 
-        let resp: api::Response<Vec<api::Tweet>, Includes> = serde_json::from_slice(&bytes)?;
+        let resp: api::Response<Vec<api::DmEvent>, DmIncludes> =
+            self.get_response(endpoint, uri).await?;
+        self.add_warnings(resp.errors);
         let next_pagination_token = resp.meta.and_then(|meta| meta.next_token);
-        let includes = resp.includes.ok_or(anyhow!("Expected `includes`"))?;
-        let users: HashMap<String, &api::User> = includes
-            .users
-            .iter()
-            .map(|user| (user.id.clone(), user))
-            .collect();
-        let tweets: Vec<api::Tweet> = resp
+        let includes = resp.includes.unwrap_or_default();
+        let dm_events = resp
             .data
-            .iter()
-            .map(|tweet| api::Tweet {
-                author_username: users
-                    .get(&tweet.author_id)
-                    .map(|user| user.username.clone()),
-                author_name: users.get(&tweet.author_id).map(|user| user.name.clone()),
-                ..tweet.clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|dm_event| {
+                let sender = includes
+                    .users
+                    .iter()
+                    .find(|user| Some(&user.id) == dm_event.sender_id.as_ref())
+                    .cloned();
+                let participants = includes
+                    .users
+                    .iter()
+                    .filter(|user| {
+                        dm_event
+                            .participant_ids
+                            .as_ref()
+                            .is_some_and(|participant_ids| participant_ids.contains(&user.id))
+                    })
+                    .cloned()
+                    .collect();
+                api::DmEvent {
+                    sender,
+                    participants,
+                    ..dm_event
+                }
             })
             .collect();
-        Ok((tweets, next_pagination_token))
+        Ok((dm_events, next_pagination_token))
     }
 
-    pub async fn user_tweets(
+    /// The DMs of every conversation, newest first.  NB: only goes back 30 days.
+    pub async fn dm_events(
+        &self,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::DmEvent>> {
+        let mut uri = self.api_url("dm_events")?;
+        self.get_dm_events("dm_events", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn dm_conversation_events(
+        &self,
+        dm_conversation_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::DmEvent>> {
+        let mut uri = self.api_url(&format!("dm_conversations/{dm_conversation_id}/dm_events"))?;
+        let endpoint = "dm_conversations/:dm_conversation_id/dm_events";
+        self.get_dm_events(endpoint, &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn send_dm(&self, dm_conversation_id: &str, text: &str) -> Result<api::CreatedDm> {
+        let uri = self.api_url(&format!("dm_conversations/{dm_conversation_id}/messages"))?;
+        let body = serde_json::json!({ "text": text });
+        let endpoint = "POST dm_conversations/:dm_conversation_id/messages";
+        let resp: api::Response<api::CreatedDm, ()> = self
+            .request_response(Method::POST, endpoint, &uri, Some(body))
+            .await?;
+        expect_data(resp)
+    }
+
+    async fn get_users(
+        &self,
+        endpoint: &'static str,
+        uri: &mut Url,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::User>> {
+        uri.query_pairs_mut()
+            .append_pair("user.fields", api::USER_FIELDS)
+            .append_pair("max_results", "1000");
+        if let Some(pagination_token) = pagination_token {
+            uri.query_pairs_mut()
+                .append_pair("pagination_token", &pagination_token);
+        }
+
+        let resp: api::Response<Vec<api::User>, ()> = self.get_response(endpoint, uri).await?;
+        self.add_warnings(resp.errors);
+        let next_pagination_token = resp.meta.and_then(|meta| meta.next_token);
+        Ok((resp.data.unwrap_or_default(), next_pagination_token))
+    }
+
+    pub async fn followers(
         &self,
         user_id: &str,
         pagination_token: Option<String>,
-    ) -> PagedResult<Vec<api::Tweet>> {
-        let mut uri = Url::parse(&format!("https://api.twitter.com/2/users/{user_id}/tweets"))?;
-        self.get_tweets_with_users(&mut uri, pagination_token).await
+    ) -> PagedResult<Vec<api::User>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/followers"))?;
+        self.get_users("users/:id/followers", &mut uri, pagination_token)
+            .await
     }
 
-    pub async fn timeline_reverse_chronological(
+    pub async fn following(
         &self,
         user_id: &str,
         pagination_token: Option<String>,
-    ) -> PagedResult<Vec<api::Tweet>> {
-        let mut uri = Url::parse(&format!(
-            "https://api.twitter.com/2/users/{user_id}/timelines/reverse_chronological"
-        ))?;
-        self.get_tweets_with_users(&mut uri, pagination_token).await
+    ) -> PagedResult<Vec<api::User>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/following"))?;
+        self.get_users("users/:id/following", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn follow_user(
+        &self,
+        user_id: &str,
+        target_user_id: &str,
+    ) -> Result<api::FollowStatus> {
+        let uri = self.api_url(&format!("users/{user_id}/following"))?;
+        let body = serde_json::json!({ "target_user_id": target_user_id });
+        let resp: api::Response<api::FollowStatus, ()> = self
+            .request_response(Method::POST, "POST users/:id/following", &uri, Some(body))
+            .await?;
+        expect_data(resp)
+    }
+
+    pub async fn unfollow_user(&self, user_id: &str, target_user_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/following/{target_user_id}"))?;
+        let endpoint = "DELETE users/:source_user_id/following/:target_user_id";
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::DELETE, endpoint, &uri, None)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn muting(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::User>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/muting"))?;
+        self.get_users("users/:id/muting", &mut uri, pagination_token)
+            .await
+    }
+
+    pub async fn mute_user(&self, user_id: &str, target_user_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/muting"))?;
+        let body = serde_json::json!({ "target_user_id": target_user_id });
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::POST, "POST users/:id/muting", &uri, Some(body))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn unmute_user(&self, user_id: &str, target_user_id: &str) -> Result<()> {
+        let uri = self.api_url(&format!("users/{user_id}/muting/{target_user_id}"))?;
+        let endpoint = "DELETE users/:source_user_id/muting/:target_user_id";
+        let _: api::Response<serde_json::Value, ()> = self
+            .request_response(Method::DELETE, endpoint, &uri, None)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn blocking(
+        &self,
+        user_id: &str,
+        pagination_token: Option<String>,
+    ) -> PagedResult<Vec<api::User>> {
+        let mut uri = self.api_url(&format!("users/{user_id}/blocking"))?;
+        self.get_users("users/:id/blocking", &mut uri, pagination_token)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter_client::mock_server::{MockResponse, MockServer};
+    use hyper::StatusCode;
+
+    fn mock_client(mock_server: &MockServer) -> TwitterClient {
+        let endpoints = TwitterEndpoints::with_base_url(&mock_server.base_url()).unwrap();
+        let mut twitter_client = TwitterClient::new_with_endpoints("id", "secret", endpoints);
+        twitter_client.set_access_token("token");
+        twitter_client.with_retry_policy(RetryPolicy::never())
+    }
+
+    #[tokio::test]
+    async fn test_status_error() {
+        let mock_server = MockServer::start().unwrap();
+        let body = r#"{"title":"Too Many Requests","detail":"Too Many Requests","type":"about:blank","status":429}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/users/me",
+            &[],
+            vec![MockResponse::json(body).with_status(StatusCode::TOO_MANY_REQUESTS)],
+        );
+        let twitter_client = mock_client(&mock_server);
+
+        let err = twitter_client.me().await.unwrap_err();
+        let api_error = err.downcast_ref::<TwitterApiError>().unwrap();
+        assert!(api_error.is_rate_limited());
+        assert_eq!(
+            api_error.to_string(),
+            "Twitter API error 429 Too Many Requests: Too Many Requests"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errors_without_data() {
+        let mock_server = MockServer::start().unwrap();
+        let body = r#"{"errors":[{"value":"nobody","detail":"Could not find user with username: [nobody].","title":"Not Found Error","resource_type":"user","parameter":"username","resource_id":"nobody","type":"https://api.twitter.com/2/problems/resource-not-found"}]}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/users/by/username/nobody",
+            &[],
+            vec![MockResponse::json(body)],
+        );
+        let twitter_client = mock_client(&mock_server);
+
+        let err = twitter_client.user_by_username("nobody").await.unwrap_err();
+        let api_error = err.downcast_ref::<TwitterApiError>().unwrap();
+        assert!(api_error.is_not_found());
+        assert_eq!(
+            api_error.to_string(),
+            "Twitter API returned errors; Could not find user with username: [nobody]."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_page() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let twitter_client = mock_client(&mock_server);
+
+        let (tweets, next_token) = twitter_client.user_tweets("1001", None).await.unwrap();
+        assert!(tweets.is_empty());
+        assert_eq!(next_token, None);
+    }
+
+    #[tokio::test]
+    async fn test_partial_errors_are_warnings() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let twitter_client = mock_client(&mock_server);
+
+        let (tweets, _) = twitter_client.user_tweets("1002", None).await.unwrap();
+        assert_eq!(tweets[0].author_username(), Some("quiet_user"));
+        let warnings = twitter_client.warnings().recent;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].resource_id.as_deref(),
+            Some("1600000000000000020")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_fields() {
+        let mock_server = MockServer::start_with_fixture_dir("fixtures").unwrap();
+        let twitter_client = mock_client(&mock_server);
+
+        let (tweets, next_token) = twitter_client.user_tweets("1003", None).await.unwrap();
+        assert_eq!(tweets[0].text, "edited");
+        assert_eq!(next_token.as_deref(), Some("editing_page_2"));
+        assert!(twitter_client.warnings().recent.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_exhausted() {
+        let mock_server = MockServer::start().unwrap();
+        let reset = (chrono::Utc::now().timestamp() + 600).to_string();
+        let body = r#"{"data":{"id":"1000","name":"Mock User","username":"mock_user"}}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/users/me",
+            &[],
+            vec![MockResponse::json(body)
+                .with_header("x-rate-limit-limit", "75")
+                .with_header("x-rate-limit-remaining", "0")
+                .with_header("x-rate-limit-reset", &reset)],
+        );
+        let twitter_client = mock_client(&mock_server);
+
+        twitter_client.me().await.unwrap();
+        let budget = twitter_client.rate_limits().budget("users/me").unwrap();
+        assert_eq!((budget.remaining, budget.limit), (0, 75));
+
+        let err = twitter_client.me().await.unwrap_err();
+        let api_error = err.downcast_ref::<TwitterApiError>().unwrap();
+        assert!(matches!(
+            api_error,
+            TwitterApiError::RateLimitExceeded { .. }
+        ));
+        assert_eq!(mock_server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_expired_access_token() {
+        let mock_server = MockServer::start().unwrap();
+        let unauthorized =
+            r#"{"title":"Unauthorized","type":"about:blank","status":401,"detail":"Unauthorized"}"#;
+        let body = r#"{"data":{"id":"1000","name":"Mock User","username":"mock_user"}}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/users/me",
+            &[],
+            vec![
+                MockResponse::json(unauthorized).with_status(StatusCode::UNAUTHORIZED),
+                MockResponse::json(unauthorized).with_status(StatusCode::UNAUTHORIZED),
+                MockResponse::json(body),
+            ],
+        );
+        let token = r#"{"token_type":"bearer","expires_in":7200,"access_token":"fresh","scope":"tweet.read users.read offline.access","refresh_token":"fresh_refresh"}"#;
+        mock_server.route(
+            Method::POST,
+            "/2/oauth2/token",
+            &[],
+            vec![MockResponse::json(token)],
+        );
+        let auth_path = std::env::temp_dir().join(format!("oauth-{}", std::process::id()));
+        let mut twitter_client = mock_client(&mock_server);
+        twitter_client.set_refresh_token("stale_refresh");
+        twitter_client.set_auth_path(&auth_path);
+
+        let (a, b) = tokio::join!(twitter_client.me(), twitter_client.me());
+        assert_eq!(a.unwrap().id, "1000");
+        assert_eq!(b.unwrap().id, "1000");
+
+        let requests = mock_server.requests();
+        let token_requests: Vec<_> = requests
+            .iter()
+            .filter(|req| req.url.path() == "/2/oauth2/token")
+            .collect();
+        assert_eq!(token_requests.len(), 1);
+        let token_request = String::from_utf8_lossy(&token_requests[0].body).to_string();
+        assert!(token_request.contains("refresh_token=stale_refresh"));
+        assert_eq!(
+            requests.last().unwrap().headers["authorization"],
+            "Bearer fresh"
+        );
+
+        let saved = fs::read_to_string(&auth_path).unwrap();
+        fs::remove_file(&auth_path).unwrap();
+        assert!(saved.contains("fresh_refresh"));
     }
 
-    pub async fn search_tweets(&self, query: &str) -> PagedResult<Vec<api::Tweet>> {
-        let mut uri = Url::parse("https://api.twitter.com/2/tweets/search/recent")?;
-        uri.query_pairs_mut().append_pair("query", query);
-        self.get_tweets_with_users(&mut uri, None).await
+    #[tokio::test]
+    async fn test_retry_over_capacity() {
+        let mock_server = MockServer::start().unwrap();
+        let over_capacity = r#"{"title":"Service Unavailable","detail":"Service Unavailable","type":"about:blank","status":503}"#;
+        let body = r#"{"data":{"id":"1000","name":"Mock User","username":"mock_user"}}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/users/me",
+            &[],
+            vec![
+                MockResponse::json(over_capacity).with_status(StatusCode::SERVICE_UNAVAILABLE),
+                MockResponse::json(over_capacity)
+                    .with_status(StatusCode::SERVICE_UNAVAILABLE)
+                    .with_header("retry-after", "0"),
+                MockResponse::json(body),
+            ],
+        );
+        let twitter_client = mock_client(&mock_server).with_retry_policy(RetryPolicy {
+            base_delay: std::time::Duration::from_millis(1),
+            ..RetryPolicy::default()
+        });
+        assert_eq!(twitter_client.me().await.unwrap().id, "1000");
+        assert_eq!(mock_server.requests().len(), 3);
+
+        mock_server.route(
+            Method::GET,
+            "/2/users/by/username/jack",
+            &[],
+            vec![MockResponse::json(over_capacity).with_status(StatusCode::SERVICE_UNAVAILABLE)],
+        );
+        let err = twitter_client
+            .with_retry_policy(RetryPolicy::never())
+            .user_by_username("jack")
+            .await
+            .unwrap_err();
+        let api_error = err.downcast_ref::<TwitterApiError>().unwrap();
+        assert_eq!(api_error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(mock_server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_reconnects() {
+        let mock_server = MockServer::start().unwrap();
+        let line = r#"{"data":{"id":"1","text":"hi","created_at":"2023-02-01T12:00:00.000Z","author_id":"12"}}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/tweets/search/stream",
+            &[],
+            vec![
+                MockResponse::stream(&format!("{line}\r\n")).stalled(),
+                MockResponse::json(r#"{"title":"Service Unavailable"}"#)
+                    .with_status(StatusCode::SERVICE_UNAVAILABLE),
+            ],
+        );
+        let twitter_client = mock_client(&mock_server)
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(10),
+            })
+            .with_stream_read_timeout(Duration::from_millis(50));
+
+        let mut tweet_ids = Vec::new();
+        let err = twitter_client
+            .filtered_stream(|tweet| tweet_ids.push(tweet.id))
+            .await
+            .unwrap_err();
+        let api_error = err.downcast_ref::<TwitterApiError>().unwrap();
+        assert_eq!(api_error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(tweet_ids, vec!["1"]);
+        assert_eq!(mock_server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_empty_stream_gives_up() {
+        let mock_server = MockServer::start().unwrap();
+        mock_server.route(
+            Method::GET,
+            "/2/tweets/search/stream",
+            &[],
+            vec![MockResponse::stream("")],
+        );
+        let twitter_client = mock_client(&mock_server).with_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        });
+
+        let err = twitter_client.filtered_stream(|_| ()).await.unwrap_err();
+        assert!(err.downcast_ref::<std::io::Error>().is_some());
+        assert_eq!(mock_server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stream_rules_use_app_access_token() {
+        let mock_server = MockServer::start().unwrap();
+        let added = r#"{"data":[{"id":"7","value":"from:jack","tag":"jack"}],"meta":{"sent":"2023-02-01T12:00:00.000Z","summary":{"created":1,"not_created":0,"valid":1,"invalid":0}}}"#;
+        mock_server.route(
+            Method::POST,
+            "/2/tweets/search/stream/rules",
+            &[],
+            vec![MockResponse::json(added)],
+        );
+        let mut twitter_client = mock_client(&mock_server);
+        twitter_client.set_app_access_token("app_token");
+
+        let new_rule = api::NewStreamRule {
+            value: "from:jack".to_string(),
+            tag: Some("jack".to_string()),
+        };
+        let rules = twitter_client.add_stream_rules(&[new_rule]).await.unwrap();
+        assert_eq!(rules[0].id, "7");
+
+        let request = mock_server.requests().pop().unwrap();
+        assert_eq!(request.headers["authorization"], "Bearer app_token");
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "add": [{ "value": "from:jack", "tag": "jack" }] })
+        );
+
+        // NB: everything else still goes as the user
+        let body = r#"{"data":{"id":"1000","name":"Mock User","username":"mock_user"}}"#;
+        mock_server.route(
+            Method::GET,
+            "/2/users/me",
+            &[],
+            vec![MockResponse::json(body)],
+        );
+        twitter_client.me().await.unwrap();
+        let request = mock_server.requests().pop().unwrap();
+        assert_eq!(request.headers["authorization"], "Bearer token");
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let body = r#"{
+            "data": [{
+                "id": "2", "text": "look", "created_at": "2023-02-01T12:00:00.000Z", "author_id": "12",
+                "attachments": {"media_keys": ["3_1"], "poll_ids": ["9"]},
+                "geo": {"place_id": "p1"},
+                "referenced_tweets": [{"type": "quoted", "id": "1"}]
+            }],
+            "includes": {
+                "users": [{"id": "12", "name": "jack", "username": "jack"}, {"id": "13", "name": "biz", "username": "biz"}],
+                "tweets": [{"id": "1", "text": "quoted", "created_at": "2023-02-01T11:00:00.000Z", "author_id": "13"}],
+                "media": [{"media_key": "3_1", "type": "photo", "width": 1200, "height": 800}],
+                "polls": [{"id": "9", "options": [{"position": 1, "label": "yes", "votes": 3}], "voting_status": "open"}],
+                "places": [{"id": "p1", "full_name": "San Francisco, CA"}]
+            }
+        }"#;
+        let resp: api::Response<Vec<api::Tweet>, TweetIncludes> =
+            serde_json::from_str(body).unwrap();
+        let tweets = resolve_includes(resp.data.unwrap(), &resp.includes.unwrap());
+
+        let tweet = &tweets[0];
+        assert_eq!(tweet.author_username(), Some("jack"));
+        assert_eq!(tweet.media.as_ref().unwrap()[0].r#type, "photo");
+        assert_eq!(tweet.poll.as_ref().unwrap().options[0].votes, 3);
+        assert_eq!(tweet.place.as_ref().unwrap().full_name, "San Francisco, CA");
+        let quoted = &tweet.included_tweets.as_ref().unwrap()[0];
+        assert_eq!(quoted.author_username(), Some("biz"));
     }
 }
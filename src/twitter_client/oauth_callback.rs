@@ -0,0 +1,171 @@
+use anyhow::{anyhow, Result};
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Request, Response, StatusCode};
+use oauth2::CsrfToken;
+use std::convert::Infallible;
+use std::process;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use url::Url;
+
+/// Tries the platform's URL opener; the URL is printed either way, for headless machines.
+pub fn open_in_browser(url: &Url) {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let opened = process::Command::new(opener)
+        .arg(url.as_str())
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+    if opened {
+        println!("Opened login page in browser, or browse to:\n{url}");
+    } else {
+        println!("Browse to this URL to login:\n{url}");
+    }
+}
+
+/// Verifies the `state` param against [csrf_token] before handing back the `code` param.
+pub fn parse_authorization_code(url: &Url, csrf_token: &CsrfToken) -> Result<String> {
+    let mut csrf_state = None;
+    let mut authorization_code = None;
+    let mut error = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "state" => csrf_state = Some(String::from(value)),
+            "code" => authorization_code = Some(String::from(value)),
+            "error" => error = Some(String::from(value)),
+            _ => (),
+        }
+    }
+
+    let csrf_state = csrf_state.ok_or(anyhow!("Missing `state` param from callback"))?;
+    if &csrf_state != csrf_token.secret() {
+        return Err(anyhow!(
+            "Callback `state` param does not match, refusing to login"
+        ));
+    }
+    if let Some(error) = error {
+        return Err(anyhow!("Authorization failed: {error}"));
+    }
+    authorization_code.ok_or(anyhow!("Missing `code` param from callback"))
+}
+
+/// Serves the OAuth redirect on [listener] until a callback arrives, then stops listening.
+pub async fn receive_authorization_code(
+    listener: TcpListener,
+    csrf_token: &CsrfToken,
+) -> Result<String> {
+    let (set_authorization_code, mut authorization_code) = mpsc::channel::<Result<String>>(1);
+    let csrf_token = csrf_token.clone();
+
+    let server = tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    eprintln!("Error accepting callback: {err}");
+                    continue;
+                }
+            };
+            let set_authorization_code = set_authorization_code.clone();
+            let csrf_token = csrf_token.clone();
+
+            tokio::spawn(async move {
+                let service = service_fn(move |req: Request<Body>| {
+                    let set_authorization_code = set_authorization_code.clone();
+                    let csrf_token = csrf_token.clone();
+                    async move {
+                        Ok::<_, Infallible>(
+                            handle_callback(req, &csrf_token, &set_authorization_code).await,
+                        )
+                    }
+                });
+                if let Err(err) = Http::new().serve_connection(stream, service).await {
+                    eprintln!("Error serving callback: {err}");
+                }
+            });
+        }
+    });
+
+    let result = authorization_code.recv().await;
+    server.abort();
+    result.ok_or(anyhow!("Callback listener stopped"))?
+}
+
+async fn handle_callback(
+    req: Request<Body>,
+    csrf_token: &CsrfToken,
+    set_authorization_code: &mpsc::Sender<Result<String>>,
+) -> Response<Body> {
+    let url = Url::parse("http://localhost/").and_then(|base| base.join(&req.uri().to_string()));
+    let url = match url {
+        // NB: ignore anything that isn't the redirect, e.g. the browser asking for a favicon
+        Ok(url) if url.query_pairs().any(|(key, _)| key == "state") => url,
+        _ => {
+            let mut response = Response::new(Body::from("Not found"));
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            return response;
+        }
+    };
+
+    let result = parse_authorization_code(&url, csrf_token);
+    let response = match &result {
+        Ok(_) => Response::new(Body::from("Logged in, you can close this window now")),
+        Err(err) => {
+            let mut response = Response::new(Body::from(format!("Login failed: {err}")));
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+            response
+        }
+    };
+    set_authorization_code.send(result).await.ok();
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_authorization_code() {
+        let csrf_token = CsrfToken::new("expected".to_string());
+
+        let url = Url::parse("http://127.0.0.1:8080/?state=expected&code=abc").unwrap();
+        assert_eq!(parse_authorization_code(&url, &csrf_token).unwrap(), "abc");
+
+        let url = Url::parse("http://127.0.0.1:8080/?state=forged&code=abc").unwrap();
+        assert!(parse_authorization_code(&url, &csrf_token).is_err());
+
+        let url = Url::parse("http://127.0.0.1:8080/?state=expected&error=access_denied").unwrap();
+        assert!(parse_authorization_code(&url, &csrf_token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_receive_authorization_code() {
+        let csrf_token = CsrfToken::new("expected".to_string());
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let browser = tokio::spawn(async move {
+            let client = hyper::Client::new();
+            let favicon = format!("http://{addr}/favicon.ico").parse().unwrap();
+            let resp = client.get(favicon).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+            let callback = format!("http://{addr}/?state=expected&code=abc")
+                .parse()
+                .unwrap();
+            let resp = client.get(callback).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        });
+
+        let authorization_code = receive_authorization_code(listener, &csrf_token)
+            .await
+            .unwrap();
+        assert_eq!(authorization_code, "abc");
+        browser.await.unwrap();
+    }
+}
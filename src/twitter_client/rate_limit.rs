@@ -0,0 +1,200 @@
+use crate::twitter_client::TwitterApiError;
+use chrono::{DateTime, TimeZone, Utc};
+use governor::clock::{Clock, DefaultClock};
+use governor::state::{InMemoryState, NotKeyed};
+use governor::{Quota, RateLimiter};
+use hyper::HeaderMap;
+use nonzero_ext::nonzero;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type DirectRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+/// All v2 rate limits are counted over 15-minute windows.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Calls that would have to wait longer than this for budget are rejected instead.
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// As reported by the `x-rate-limit-*` headers of the latest response from an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitBudget {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimitBudget {
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        fn parse_header<T: FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+            headers.get(name)?.to_str().ok()?.parse().ok()
+        }
+
+        let reset: i64 = parse_header(headers, "x-rate-limit-reset")?;
+        Some(Self {
+            limit: parse_header(headers, "x-rate-limit-limit")?,
+            remaining: parse_header(headers, "x-rate-limit-remaining")?,
+            reset: Utc.timestamp_opt(reset, 0).single()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct EndpointRateLimit {
+    budget: RateLimitBudget,
+    // NB: the headers only reflect requests that have already returned; the limiter also counts
+    // the ones still in flight, e.g. from parallel feed tasks.
+    limiter: Arc<DirectRateLimiter>,
+}
+
+impl EndpointRateLimit {
+    fn new(budget: RateLimitBudget) -> Self {
+        let limit = NonZeroU32::new(budget.limit).unwrap_or(nonzero!(1u32));
+        let quota = Quota::with_period(RATE_LIMIT_WINDOW / limit.get())
+            .unwrap()
+            .allow_burst(limit);
+        let limiter = RateLimiter::direct(quota);
+        if let Some(used) = NonZeroU32::new(budget.limit.saturating_sub(budget.remaining)) {
+            limiter.check_n(used).ok();
+        }
+        Self {
+            budget,
+            limiter: Arc::new(limiter),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RateLimitState {
+    endpoints: HashMap<&'static str, EndpointRateLimit>,
+    last_endpoint: Option<&'static str>,
+}
+
+/// Per-endpoint rate limit tracking, keyed by path template (e.g. `users/:id/tweets`).
+#[derive(Debug)]
+pub struct RateLimits {
+    max_delay: Mutex<Duration>,
+    state: Mutex<RateLimitState>,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            max_delay: Mutex::new(DEFAULT_MAX_DELAY),
+            state: Mutex::new(RateLimitState::default()),
+        }
+    }
+}
+
+impl RateLimits {
+    pub fn set_max_delay(&self, max_delay: Duration) {
+        *self.max_delay.lock().unwrap() = max_delay;
+    }
+
+    pub fn budget(&self, endpoint: &str) -> Option<RateLimitBudget> {
+        let state = self.state.lock().unwrap();
+        state.endpoints.get(endpoint).map(|e| e.budget)
+    }
+
+    /// The budget of whichever endpoint was called most recently.
+    pub fn last_budget(&self) -> Option<(&'static str, RateLimitBudget)> {
+        let state = self.state.lock().unwrap();
+        let endpoint = state.last_endpoint?;
+        state.endpoints.get(endpoint).map(|e| (endpoint, e.budget))
+    }
+
+    /// Waits until [endpoint] has budget for one more call, or fails if that would take longer
+    /// than the max delay.  Endpoints that haven't reported a budget yet are let through.
+    pub async fn acquire(&self, endpoint: &'static str) -> Result<(), TwitterApiError> {
+        let max_delay = *self.max_delay.lock().unwrap();
+        let (budget, limiter) = {
+            let state = self.state.lock().unwrap();
+            match state.endpoints.get(endpoint) {
+                Some(e) => (e.budget, e.limiter.clone()),
+                None => return Ok(()),
+            }
+        };
+        let exceeded = || TwitterApiError::RateLimitExceeded {
+            endpoint: endpoint.to_string(),
+            reset: budget.reset,
+        };
+
+        let now = Utc::now();
+        if budget.remaining == 0 && budget.reset > now {
+            let wait = (budget.reset - now).to_std().unwrap_or_default();
+            if wait > max_delay {
+                return Err(exceeded());
+            }
+            tokio::time::sleep(wait).await;
+        } else if let Err(not_until) = limiter.check() {
+            let wait = not_until.wait_time_from(DefaultClock::default().now());
+            if wait > max_delay {
+                return Err(exceeded());
+            }
+            limiter.until_ready().await;
+        }
+        Ok(())
+    }
+
+    pub fn update(&self, endpoint: &'static str, headers: &HeaderMap) {
+        if let Some(budget) = RateLimitBudget::from_headers(headers) {
+            let mut state = self.state.lock().unwrap();
+            state.last_endpoint = Some(endpoint);
+            match state.endpoints.get_mut(endpoint) {
+                // NB: a new window, or budget handed back, means the limiter's count is stale
+                Some(e)
+                    if e.budget.limit == budget.limit
+                        && budget.reset <= e.budget.reset
+                        && budget.remaining <= e.budget.remaining =>
+                {
+                    e.budget = budget
+                }
+                _ => {
+                    state
+                        .endpoints
+                        .insert(endpoint, EndpointRateLimit::new(budget));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(limit: u32, remaining: u32, reset: DateTime<Utc>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rate-limit-limit", limit.into());
+        headers.insert("x-rate-limit-remaining", remaining.into());
+        headers.insert("x-rate-limit-reset", reset.timestamp().into());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_new_window_resyncs_limiter() {
+        let rate_limits = RateLimits::default();
+        let now = Utc::now();
+
+        rate_limits.update(
+            "users/me",
+            &headers(2, 1, now + chrono::Duration::minutes(10)),
+        );
+        rate_limits.acquire("users/me").await.unwrap();
+        rate_limits.update(
+            "users/me",
+            &headers(2, 0, now + chrono::Duration::minutes(10)),
+        );
+        assert!(rate_limits.acquire("users/me").await.is_err());
+
+        rate_limits.update(
+            "users/me",
+            &headers(2, 2, now + chrono::Duration::minutes(25)),
+        );
+        rate_limits.acquire("users/me").await.unwrap();
+        rate_limits.acquire("users/me").await.unwrap();
+    }
+}
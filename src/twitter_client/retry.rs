@@ -0,0 +1,157 @@
+use crate::twitter_client::RateLimitBudget;
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use hyper::body::Bytes;
+use hyper::{HeaderMap, Response, StatusCode};
+use rand::Rng;
+use std::time::Duration;
+
+/// How [TwitterClient] retries transient failures: connection errors, 5xx (including 503 "over
+/// capacity") and 429s whose reset is close enough to wait out.
+///
+/// [TwitterClient]: crate::twitter_client::TwitterClient
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first; 1 means never retry.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Backoff is capped at this, and a `Retry-After` or rate limit reset further out than this
+    /// is not waited for at all.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Returns how long to wait before attempting again after [attempt] (1-based) produced
+    /// [result], or [None] if [result] should be returned as is.
+    pub fn retry_delay(
+        &self,
+        attempt: u32,
+        result: &Result<Response<Bytes>, Error>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        match result {
+            Ok(resp) => {
+                let status = resp.status();
+                let requested_delay = retry_after(resp.headers()).or_else(|| {
+                    (status == StatusCode::TOO_MANY_REQUESTS)
+                        .then(|| RateLimitBudget::from_headers(resp.headers()))
+                        .flatten()
+                        .map(|budget| until(budget.reset))
+                });
+
+                if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                    match requested_delay {
+                        Some(delay) if delay > self.max_delay => None,
+                        Some(delay) => Some(delay),
+                        None => Some(self.backoff(attempt)),
+                    }
+                } else {
+                    None
+                }
+            }
+            Err(err) if is_transient(err) => Some(self.backoff(attempt)),
+            Err(_) => None,
+        }
+    }
+
+    /// Exponential backoff with jitter, so that parallel tasks don't retry in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        rand::thread_rng().gen_range(delay / 2..=delay)
+    }
+}
+
+fn until(time: DateTime<Utc>) -> Duration {
+    (time - Utc::now()).to_std().unwrap_or_default()
+}
+
+/// NB: `Retry-After` may be either delay-seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get("retry-after")?.to_str().ok()?;
+    if let Ok(seconds) = value.parse::<u64>() {
+        Some(Duration::from_secs(seconds))
+    } else {
+        let time = DateTime::parse_from_rfc2822(value).ok()?;
+        Some(until(time.with_timezone(&Utc)))
+    }
+}
+
+fn is_transient(err: &Error) -> bool {
+    if let Some(err) = err.downcast_ref::<hyper::Error>() {
+        !err.is_user() && !err.is_parse()
+    } else {
+        err.downcast_ref::<std::io::Error>().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn response(status: StatusCode, headers: &[(&str, &str)]) -> Result<Response<Bytes>, Error> {
+        let mut builder = Response::builder().status(status);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        Ok(builder.body(Bytes::new()).unwrap())
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::default();
+        let unavailable = response(StatusCode::SERVICE_UNAVAILABLE, &[]);
+        let delay = policy.retry_delay(1, &unavailable).unwrap();
+        assert!(delay >= policy.base_delay / 2 && delay <= policy.base_delay);
+        assert_eq!(policy.retry_delay(policy.max_attempts, &unavailable), None);
+
+        let retry_after = response(StatusCode::SERVICE_UNAVAILABLE, &[("retry-after", "3")]);
+        assert_eq!(
+            policy.retry_delay(1, &retry_after),
+            Some(Duration::from_secs(3))
+        );
+
+        let reset = (Utc::now().timestamp() + 600).to_string();
+        let rate_limited = response(
+            StatusCode::TOO_MANY_REQUESTS,
+            &[
+                ("x-rate-limit-limit", "15"),
+                ("x-rate-limit-remaining", "0"),
+                ("x-rate-limit-reset", &reset),
+            ],
+        );
+        assert_eq!(policy.retry_delay(1, &rate_limited), None);
+
+        assert_eq!(policy.retry_delay(1, &response(StatusCode::OK, &[])), None);
+        assert_eq!(
+            policy.retry_delay(1, &response(StatusCode::BAD_REQUEST, &[])),
+            None
+        );
+        assert_eq!(policy.retry_delay(1, &Err(anyhow!("Unauthorized"))), None);
+        assert_eq!(RetryPolicy::never().retry_delay(1, &unavailable), None);
+    }
+}
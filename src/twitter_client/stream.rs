@@ -0,0 +1,70 @@
+use crate::twitter_client::{api, resolve_includes, TweetIncludes, TwitterApiError};
+use anyhow::Result;
+use hyper::StatusCode;
+
+/// Reassembles the lines of the filtered stream from however the body happens to be chunked.
+///
+/// NB: Twitter separates tweets with `\r\n`, and sends a bare `\r\n` every 20 seconds or so to
+/// keep the connection alive.
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    buffer: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Returns the complete, non-blank lines that [chunk] finishes.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut lines = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            if !line.trim_ascii().is_empty() {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+}
+
+/// Decodes one line of the stream, or [None] if the line only carried `errors`; those precede a
+/// disconnect, which is handled by reconnecting.
+pub fn decode_line(line: &[u8]) -> Result<Option<api::Tweet>> {
+    let resp = TwitterApiError::parse_response::<api::Response<api::Tweet, TweetIncludes>>(
+        StatusCode::OK,
+        line,
+    )?;
+    let includes = resp.includes.unwrap_or_default();
+    Ok(resolve_includes(resp.data.into_iter().collect(), &includes).pop())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffer() {
+        let mut line_buffer = LineBuffer::default();
+        assert!(line_buffer.push(b"{\"data\":").is_empty());
+        assert_eq!(
+            line_buffer.push(b"1}\r\n\r\n{\"data\":2}\r\n{"),
+            vec![b"{\"data\":1}\r\n".to_vec(), b"{\"data\":2}\r\n".to_vec()]
+        );
+        assert_eq!(
+            line_buffer.push(b"\"data\":3}\r\n"),
+            vec![b"{\"data\":3}\r\n".to_vec()]
+        );
+        assert!(line_buffer.push(b"\r\n").is_empty());
+    }
+
+    #[test]
+    fn test_decode_line() {
+        let line = br#"{"data":{"id":"1","text":"hi","created_at":"2023-02-01T12:00:00.000Z","author_id":"12"},"includes":{"users":[{"id":"12","name":"jack","username":"jack"}]},"matching_rules":[{"id":"7","tag":"jack"}]}"#;
+        let tweet = decode_line(line).unwrap().unwrap();
+        assert_eq!(tweet.author_username(), Some("jack"));
+
+        let line = br#"{"errors":[{"title":"operational-disconnect","type":"https://api.twitter.com/2/problems/operational-disconnect"}]}"#;
+        assert!(decode_line(line).unwrap().is_none());
+        assert!(decode_line(b"not json").is_err());
+    }
+}
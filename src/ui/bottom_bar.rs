@@ -1,6 +1,8 @@
 use crate::store::Store;
+use crate::twitter_client::RateLimitBudget;
 use crate::ui_framework::{bounding_box::BoundingBox, Input, Render};
 use anyhow::Result;
+use chrono::Local;
 use crossterm::event::KeyEvent;
 use crossterm::style::Color;
 use crossterm::terminal::{self, ClearType};
@@ -11,7 +13,11 @@ use std::sync::Arc;
 pub struct BottomBar {
     store: Arc<Store>,
     num_tasks_in_flight: usize,
+    message: Option<String>,
     should_render: bool,
+    // NB: warnings and budgets are updated from tasks, so compare rather than wait to be told
+    rendered_warnings_generation: u64,
+    rendered_budget: Option<(&'static str, RateLimitBudget)>,
 }
 
 impl BottomBar {
@@ -19,7 +25,10 @@ impl BottomBar {
         Self {
             store: store.clone(),
             num_tasks_in_flight: 0,
+            message: None,
             should_render: true,
+            rendered_warnings_generation: 0,
+            rendered_budget: None,
         }
     }
 
@@ -27,11 +36,18 @@ impl BottomBar {
         self.num_tasks_in_flight = n;
         self.should_render = true;
     }
+
+    pub fn set_message(&mut self, message: Option<String>) {
+        self.message = message;
+        self.should_render = true;
+    }
 }
 
 impl Render for BottomBar {
     fn should_render(&self) -> bool {
         self.should_render
+            || self.store.twitter_client.warnings_generation() != self.rendered_warnings_generation
+            || self.store.twitter_client.rate_limits().last_budget() != self.rendered_budget
     }
 
     fn invalidate(&mut self) {
@@ -53,6 +69,32 @@ impl Render for BottomBar {
             )?;
         }
         queue!(stdout, style::Print(format!("{feed_length} tweets")))?;
+        self.rendered_budget = self.store.twitter_client.rate_limits().last_budget();
+        if let Some((endpoint, budget)) = self.rendered_budget {
+            let reset = budget.reset.with_timezone(&Local).format("%H:%M");
+            queue!(
+                stdout,
+                style::Print(format!(
+                    " | {endpoint} {}/{} until {reset}",
+                    budget.remaining, budget.limit
+                ))
+            )?;
+        }
+        let warnings = self.store.twitter_client.warnings();
+        self.rendered_warnings_generation = warnings.generation;
+        if let Some(warning) = warnings.recent.last() {
+            queue!(
+                stdout,
+                style::Print(format!(
+                    " | {} partial errors, last: {}",
+                    warnings.count,
+                    warning.description()
+                ))
+            )?;
+        }
+        if let Some(message) = &self.message {
+            queue!(stdout, style::Print(format!(" | {message}")))?;
+        }
         queue!(stdout, style::ResetColor)?;
         queue!(stdout, terminal::Clear(ClearType::UntilNewLine))?;
 
@@ -0,0 +1,410 @@
+use crate::store::Store;
+use crate::twitter_client::api;
+use crate::ui::rich_text::RichText;
+use crate::ui::search_bar::SearchBar;
+use crate::ui::InternalEvent;
+use crate::ui_framework::bounding_box::BoundingBox;
+use crate::ui_framework::scroll_buffer::{ScrollBuffer, TextSegment};
+use crate::ui_framework::{Component, Input, Render};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::style::{Color, Colors};
+use crossterm::{cursor, queue, style};
+use std::collections::{HashMap, HashSet};
+use std::io::{Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::UnboundedSender;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Focus {
+    Conversations,
+    Messages,
+    ComposeBar,
+}
+
+/// The DM inbox: [Store::dm_conversations] on the left, and the selected conversation's
+/// [Store::dm_events] on the right.
+pub struct DmPane {
+    events: UnboundedSender<InternalEvent>,
+    store: Arc<Store>,
+    conversations: ScrollBuffer,
+    messages: ScrollBuffer,
+    should_update_scroll_buffer: Arc<AtomicBool>,
+    should_render: bool,
+    display_width: usize,
+    focus: Focus,
+    dm_conversation_selected_id: Option<String>,
+    // NB: conversations whose own history was loaded, beyond what the inbox had; only recorded
+    // once that succeeds, so that a failed load is tried again
+    loaded_dm_conversation_ids: Arc<Mutex<HashSet<String>>>,
+    compose_bar: Component<SearchBar>,
+}
+
+impl DmPane {
+    pub fn new(events: &UnboundedSender<InternalEvent>, store: &Arc<Store>) -> Self {
+        Self {
+            events: events.clone(),
+            store: store.clone(),
+            conversations: ScrollBuffer::new(),
+            messages: ScrollBuffer::new(),
+            should_update_scroll_buffer: Arc::new(AtomicBool::new(true)),
+            should_render: true,
+            display_width: 0,
+            focus: Focus::Conversations,
+            dm_conversation_selected_id: None,
+            loaded_dm_conversation_ids: Arc::new(Mutex::new(HashSet::new())),
+            compose_bar: Component::new(SearchBar::with_prompt("dm> ")),
+        }
+    }
+
+    fn get_selected_dm_conversation_id(&self) -> Option<String> {
+        let line_no = self.conversations.get_cursor_line();
+        let dm_conversations = self.store.dm_conversations.lock().unwrap();
+        dm_conversations
+            .get(line_no)
+            .map(|dm_conversation| dm_conversation.id.clone())
+    }
+
+    pub fn do_load_dm_events(&self) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            match store.load_dm_events().await {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn do_load_dm_conversation(&self, dm_conversation_id: &str) {
+        if self
+            .loaded_dm_conversation_ids
+            .lock()
+            .unwrap()
+            .contains(dm_conversation_id)
+        {
+            return;
+        }
+
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+        let loaded_dm_conversation_ids = self.loaded_dm_conversation_ids.clone();
+        let dm_conversation_id = dm_conversation_id.to_string();
+
+        let task = tokio::spawn(async move {
+            match store.load_dm_conversation(&dm_conversation_id).await {
+                Ok(()) => {
+                    loaded_dm_conversation_ids
+                        .lock()
+                        .unwrap()
+                        .insert(dm_conversation_id);
+                    should_update_scroll_buffer.store(true, Ordering::SeqCst);
+                }
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn do_send_dm(&self, text: String) {
+        let Some(dm_conversation_id) = self.dm_conversation_selected_id.clone() else {
+            return;
+        };
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            match store.send_dm(&dm_conversation_id, &text).await {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn update_selection(&mut self) {
+        let selected_id = self.get_selected_dm_conversation_id();
+        if selected_id != self.dm_conversation_selected_id {
+            self.dm_conversation_selected_id = selected_id.clone();
+            if let Some(dm_conversation_id) = selected_id {
+                self.do_load_dm_conversation(&dm_conversation_id);
+            }
+            self.should_update_scroll_buffer
+                .store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn update_scroll_buffers(&mut self) {
+        let self_id = &self.store.twitter_user.id;
+        let self_username = &self.store.twitter_user.username;
+        let width = self.display_width;
+        let colors_dim = Colors::new(Color::DarkGrey, Color::Reset);
+        let colors_username = Colors::new(Color::DarkCyan, Color::Reset);
+
+        {
+            let dm_conversations = self.store.dm_conversations.lock().unwrap();
+            let dm_events = self.store.dm_events.lock().unwrap();
+            let users = self.store.users.lock().unwrap();
+
+            self.conversations.clear();
+            if dm_conversations.is_empty() {
+                self.conversations
+                    .push(vec![TextSegment::color("No DMs (yet)", colors_dim)]);
+            }
+            for dm_conversation in dm_conversations.iter() {
+                let str_participants = dm_conversation
+                    .participant_ids
+                    .iter()
+                    .filter(|participant_id| *participant_id != self_id)
+                    .map(|participant_id| draw_username(&users, participant_id))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let str_time = dm_conversation
+                    .updated_at
+                    .map(|updated_at| updated_at.format("%m-%d %H:%M ").to_string())
+                    .unwrap_or_default();
+                let str_last_text = dm_events
+                    .get(&dm_conversation.id)
+                    .and_then(|dm_events| dm_events.iter().rev().find_map(|e| e.text.as_ref()))
+                    .map(|text| text.replace(['\r', '\n'], " "))
+                    .unwrap_or_default();
+
+                let str_participants = format!("{str_participants} ");
+                let remaining_width = width
+                    .saturating_sub(str_participants.chars().count())
+                    .saturating_sub(str_time.chars().count());
+                self.conversations.push(vec![
+                    TextSegment::color(&str_participants, colors_username),
+                    TextSegment::color(&str_time, colors_dim),
+                    TextSegment::plain(
+                        &str_last_text
+                            .chars()
+                            .take(remaining_width)
+                            .collect::<String>(),
+                    ),
+                ]);
+            }
+            let y = self.conversations.get_cursor_line();
+            self.conversations.move_cursor_to(0, y);
+
+            self.messages.clear();
+            let selected_events = self
+                .dm_conversation_selected_id
+                .as_ref()
+                .and_then(|dm_conversation_id| dm_events.get(dm_conversation_id));
+            for dm_event in selected_events.into_iter().flatten() {
+                let str_sender = dm_event
+                    .sender_id
+                    .as_ref()
+                    .map(|sender_id| draw_username(&users, sender_id))
+                    .unwrap_or("[unknown]".to_string());
+                let str_time = dm_event
+                    .created_at
+                    .map(|created_at| created_at.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+
+                match (dm_event.event_type.as_str(), &dm_event.text) {
+                    ("MessageCreate", Some(text)) => {
+                        self.messages.push(vec![
+                            TextSegment::color(&format!("{str_sender} "), colors_username),
+                            TextSegment::color(&str_time, colors_dim),
+                        ]);
+                        let rich_text = RichText::from_text(text, self_username);
+                        for line in rich_text.wrap(self.messages_width()) {
+                            self.messages.push(line);
+                        }
+                    }
+                    (event_type, _) => {
+                        let participants = dm_event
+                            .participant_ids
+                            .iter()
+                            .flatten()
+                            .map(|participant_id| draw_username(&users, participant_id))
+                            .collect::<Vec<String>>()
+                            .join(", ");
+                        let verb = match event_type {
+                            "ParticipantsJoin" => "joined",
+                            "ParticipantsLeave" => "left",
+                            _ => event_type,
+                        };
+                        self.messages.push(vec![TextSegment::color(
+                            &format!("{participants} {verb} {str_time}"),
+                            colors_dim,
+                        )]);
+                    }
+                }
+                self.messages.push_newline();
+            }
+        }
+
+        // NB: newest at the bottom, like a chat
+        let last_line = self.messages.height().saturating_sub(1);
+        self.messages.move_cursor_to(0, last_line);
+        self.should_update_scroll_buffer
+            .store(false, Ordering::SeqCst);
+    }
+
+    /// The messages are drawn right of the conversations, inside a margin on either side.
+    fn messages_width(&self) -> usize {
+        self.display_width.saturating_sub(2)
+    }
+
+    fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+        self.handle_focus();
+        self.should_render = true;
+    }
+}
+
+fn draw_username(users: &HashMap<String, api::User>, user_id: &str) -> String {
+    match users.get(user_id) {
+        Some(user) => format!("@{}", user.username),
+        None => format!("<user id: {user_id}>"),
+    }
+}
+
+impl Render for DmPane {
+    fn should_render(&self) -> bool {
+        self.should_update_scroll_buffer.load(Ordering::SeqCst)
+            || self.conversations.should_render()
+            || self.messages.should_render()
+            || self.compose_bar.component.should_render()
+            || self.should_render
+    }
+
+    fn invalidate(&mut self) {
+        self.conversations.invalidate();
+        self.messages.invalidate();
+        self.compose_bar.component.invalidate();
+        self.should_render = true;
+    }
+
+    fn render(&mut self, stdout: &mut Stdout, bounding_box: BoundingBox) -> Result<()> {
+        let BoundingBox {
+            left, top, width, ..
+        } = bounding_box;
+        let half_width = ((width as usize) / 2).saturating_sub(1);
+
+        if self.should_update_scroll_buffer.load(Ordering::SeqCst)
+            || self.display_width != half_width
+        {
+            self.display_width = half_width;
+            self.update_scroll_buffers();
+            // NB: e.g. the inbox just loaded, so that there's now something to select
+            self.update_selection();
+        }
+
+        // CR: need a generic [clear] method
+        if self.should_render {
+            let str_clear = " ".repeat(width as usize);
+            for delta in 0..bounding_box.height {
+                queue!(stdout, cursor::MoveTo(left, top + delta))?;
+                queue!(stdout, style::Print(&str_clear))?;
+            }
+            self.conversations.invalidate();
+            self.messages.invalidate();
+            self.compose_bar.component.invalidate();
+        }
+
+        self.conversations.render(
+            stdout,
+            BoundingBox {
+                width: half_width as u16,
+                ..bounding_box
+            },
+        )?;
+
+        let messages_box = BoundingBox {
+            left: left + (half_width as u16) + 1,
+            width: self.messages_width() as u16,
+            ..bounding_box
+        };
+        if self.focus == Focus::ComposeBar {
+            self.compose_bar.bounding_box = BoundingBox {
+                height: 1,
+                ..messages_box
+            };
+            self.compose_bar.render_if_necessary(stdout)?;
+            self.messages.render(
+                stdout,
+                BoundingBox {
+                    top: messages_box.top + 2,
+                    height: messages_box.height.saturating_sub(2),
+                    ..messages_box
+                },
+            )?;
+        } else {
+            self.messages.render(stdout, messages_box)?;
+        }
+
+        self.should_render = false;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn get_cursor(&self) -> (u16, u16) {
+        match self.focus {
+            Focus::Conversations => self.conversations.get_cursor(),
+            Focus::Messages => {
+                let (x, y) = self.messages.get_cursor();
+                (x + self.display_width as u16 + 1, y)
+            }
+            Focus::ComposeBar => {
+                let (x, y) = self.compose_bar.component.get_cursor();
+                (x + self.display_width as u16 + 1, y)
+            }
+        }
+    }
+}
+
+impl Input for DmPane {
+    fn handle_focus(&mut self) {
+        match self.focus {
+            Focus::Conversations => self.conversations.handle_focus(),
+            Focus::Messages => self.messages.handle_focus(),
+            Focus::ComposeBar => self.compose_bar.component.handle_focus(),
+        }
+    }
+
+    fn handle_key_event(&mut self, event: &KeyEvent) -> bool {
+        match (self.focus, event.code) {
+            (Focus::ComposeBar, KeyCode::Esc) => {
+                self.compose_bar.component.clear();
+                self.set_focus(Focus::Messages);
+            }
+            (Focus::ComposeBar, KeyCode::Enter) => {
+                let text = self.compose_bar.component.get_text();
+                if !text.trim().is_empty() {
+                    self.do_send_dm(text);
+                }
+                self.compose_bar.component.clear();
+                self.set_focus(Focus::Messages);
+            }
+            (Focus::ComposeBar, _) => return self.compose_bar.component.handle_key_event(event),
+            (Focus::Conversations, KeyCode::Tab) => self.set_focus(Focus::Messages),
+            (Focus::Messages, KeyCode::Tab) => self.set_focus(Focus::Conversations),
+            (_, KeyCode::Char('r')) => self.do_load_dm_events(),
+            (_, KeyCode::Char('c')) => {
+                if self.dm_conversation_selected_id.is_some() {
+                    self.set_focus(Focus::ComposeBar);
+                }
+            }
+            (Focus::Conversations, _) => {
+                let handled = self.conversations.handle_key_event(event);
+                self.update_selection();
+                return handled;
+            }
+            (Focus::Messages, _) => return self.messages.handle_key_event(event),
+        }
+        true
+    }
+}
@@ -1,11 +1,15 @@
-use crate::store::Store;
+use crate::store::{self, Engagement, FeedSource, Store, UserListSource};
 use crate::twitter_client::api;
+use crate::ui::list_picker::ListPicker;
+use crate::ui::rich_text::RichText;
 use crate::ui::search_bar::SearchBar;
 use crate::ui::tweet_pane::TweetPane;
+use crate::ui::user_list_pane::UserListPane;
 use crate::ui::InternalEvent;
 use crate::ui_framework::scroll_buffer::{ScrollBuffer, TextSegment};
 use crate::ui_framework::{bounding_box::BoundingBox, Component, Input, Render};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use crossterm::event::{KeyCode, KeyEvent};
 use crossterm::style::{Color, Colors};
 use crossterm::{cursor, queue, style};
@@ -15,6 +19,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::{fs, process};
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
@@ -22,6 +27,18 @@ enum Focus {
     FeedPane,
     TweetPaneStack,
     SearchBar,
+    ComposeBar,
+    ListPicker,
+    UserList,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Compose {
+    Tweet,
+    Reply(String),
+    Quote(String),
+    // NB: not a tweet, but typed in the same bar; see [UserConfig::toggle_muted_keyword]
+    MuteKeyword,
 }
 
 pub struct FeedPane {
@@ -35,6 +52,13 @@ pub struct FeedPane {
     tweet_selected_id: String,
     tweet_pane: Component<TweetPane>,
     search_bar: Component<SearchBar>,
+    compose_bar: Component<SearchBar>,
+    compose: Compose,
+    list_picker: Component<ListPicker>,
+    user_list_pane: Component<UserListPane>,
+    stream_task: Option<JoinHandle<()>>,
+    // NB: muted tweets are collapsed to a line, rather than omitted, so lines still match the feed
+    show_muted: bool,
 }
 
 impl FeedPane {
@@ -42,6 +66,9 @@ impl FeedPane {
         let tweet_selected_id = String::from("0");
         let tweet_pane = Component::new(TweetPane::new(events, store, &tweet_selected_id));
         let search_bar = Component::new(SearchBar::new());
+        let compose_bar = Component::new(SearchBar::new());
+        let list_picker = Component::new(ListPicker::new(events, store));
+        let user_list_pane = Component::new(UserListPane::new(events, store));
 
         Self {
             events: events.clone(),
@@ -54,6 +81,12 @@ impl FeedPane {
             tweet_selected_id,
             tweet_pane,
             search_bar,
+            compose_bar,
+            compose: Compose::Tweet,
+            list_picker,
+            user_list_pane,
+            stream_task: None,
+            show_muted: false,
         }
     }
 
@@ -74,8 +107,16 @@ impl FeedPane {
         let tweets = self.store.tweets.lock().unwrap();
         let tweets_reverse_chronological = self.store.tweets_feed.lock().unwrap();
         let user_config = self.store.user_config.lock().unwrap();
+        let liked_tweets = self.store.liked_tweets.lock().unwrap();
+        let retweeted_tweets = self.store.retweeted_tweets.lock().unwrap();
+        let bookmarked_tweets = self.store.bookmarked_tweets.lock().unwrap();
+        let muted_users = self.store.muted_users.lock().unwrap();
+        let markers = [
+            (&*liked_tweets, "♥ ", Color::Red),
+            (&*retweeted_tweets, "⟲ ", Color::Green),
+            (&*bookmarked_tweets, "⚑ ", Color::Blue),
+        ];
 
-        let re_newlines = Regex::new(r"[\r\n]+").unwrap();
         let str_unknown = String::from("[unknown]");
 
         for tweet_id in tweets_reverse_chronological.iter() {
@@ -89,7 +130,7 @@ impl FeedPane {
                 Colors::new(Color::DarkGrey, Color::Reset),
             ));
 
-            let tweet_author = tweet.author_username.as_ref().unwrap_or(&str_unknown);
+            let tweet_author = tweet.author_username().unwrap_or(&str_unknown);
             let tweet_author = format!("@{tweet_author} ");
             let is_starred = user_config.is_starred(&tweet.author_id);
             segments.push(TextSegment::color(
@@ -101,25 +142,48 @@ impl FeedPane {
                 },
             ));
 
-            let formatted = re_newlines.replace_all(&tweet.text, "⏎ ");
-            let used_length = tweet_time.len() + tweet_author.len();
-            let remaining_length = self.display_width.saturating_sub(used_length);
-            let lines = textwrap::wrap(&formatted, remaining_length);
-            if lines.len() == 1 {
-                segments.push(TextSegment::plain(&lines[0]));
-            } else if lines.len() > 1 {
-                // Rewrap lines to accommodate ellipsis (…), which may knock out a word
-                let remaining_length = remaining_length.saturating_sub(1) as usize;
-                let lines = textwrap::wrap(&formatted, remaining_length);
-                segments.push(TextSegment::plain(&lines[0]));
-                segments.push(TextSegment::plain("…"));
+            if store::is_muted_by(&user_config, &muted_users, tweet) && !self.show_muted {
+                segments.push(TextSegment::color(
+                    "[muted]",
+                    Colors::new(Color::DarkGrey, Color::Reset),
+                ));
+                self.scroll_buffer.push(segments);
+                continue;
             }
 
+            let mut marker_length = 0;
+            // NB: long-form tweets only show their first 280 characters elsewhere
+            if tweet.note_tweet.is_some() {
+                segments.push(TextSegment::color(
+                    "¶ ",
+                    Colors::new(Color::DarkGrey, Color::Reset),
+                ));
+                marker_length += 2;
+            }
+            for (engaged_tweets, marker, color) in &markers {
+                if engaged_tweets.contains(tweet_id) {
+                    segments.push(TextSegment::color(
+                        marker,
+                        Colors::new(*color, Color::Reset),
+                    ));
+                    marker_length += marker.chars().count();
+                }
+            }
+
+            let rich_text = RichText::from_tweet(tweet, &self.store.twitter_user.username);
+            let used_length = tweet_time.len() + tweet_author.len() + marker_length;
+            let remaining_length = self.display_width.saturating_sub(used_length);
+            segments.append(&mut rich_text.one_line(remaining_length));
+
             self.scroll_buffer.push(segments);
         }
 
-        let y = self.scroll_buffer.get_cursor().1;
-        self.scroll_buffer.move_cursor_to(16, y as usize);
+        // NB: keep the selected tweet under the cursor, e.g. as streamed tweets arrive on top
+        let line_no = tweets_reverse_chronological
+            .iter()
+            .position(|tweet_id| tweet_id == &self.tweet_selected_id)
+            .unwrap_or(self.scroll_buffer.get_cursor_line());
+        self.scroll_buffer.move_cursor_to(16, line_no);
         self.should_update_scroll_buffer
             .store(false, Ordering::SeqCst);
     }
@@ -130,7 +194,27 @@ impl FeedPane {
         let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
 
         let task = tokio::spawn(async move {
-            match store.load_tweets_reverse_chronological(restart).await {
+            let result = if restart {
+                store.load_tweets_reverse_chronological(true).await
+            } else {
+                store.load_tweets_feed_next_page().await
+            };
+            match result {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn do_load_feed(&self, source: FeedSource) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            match store.load_feed(&source, true).await {
                 Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
                 Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
             }
@@ -139,6 +223,73 @@ impl FeedPane {
         self.events.send(InternalEvent::RegisterTask(task)).unwrap();
     }
 
+    /// NB: the stream task isn't registered with the UI, since it doesn't finish until the
+    /// stream gives up reconnecting.
+    fn do_follow_stream(&mut self) {
+        let is_streaming = self
+            .stream_task
+            .as_ref()
+            .is_some_and(|stream_task| !stream_task.is_finished());
+        if !is_streaming {
+            let events = self.events.clone();
+            let store = self.store.clone();
+            let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+            self.stream_task = Some(tokio::spawn(async move {
+                let on_update = || should_update_scroll_buffer.store(true, Ordering::SeqCst);
+                if let Err(error) = store.follow_stream(on_update).await {
+                    events.send(InternalEvent::LogError(error)).unwrap();
+                }
+            }));
+        }
+
+        self.do_load_feed(FeedSource::Stream);
+    }
+
+    fn start_list_picker(&mut self) {
+        self.list_picker.component.do_load_lists();
+        self.list_picker.component.invalidate();
+        self.focus = Focus::ListPicker;
+        self.handle_focus();
+        self.should_render = true;
+    }
+
+    fn return_to_feed_pane(&mut self) {
+        self.focus = Focus::FeedPane;
+        self.handle_focus();
+        self.scroll_buffer.invalidate();
+        self.should_render = true;
+    }
+
+    fn start_user_list(&mut self, followers: bool) {
+        let Some(tweet_id) = self.get_selected_tweet_id() else {
+            return;
+        };
+        let Some(author_id) = self
+            .store
+            .tweets
+            .lock()
+            .unwrap()
+            .get(&tweet_id)
+            .map(|tweet| tweet.author_id.clone())
+        else {
+            return;
+        };
+
+        let source = if followers {
+            UserListSource::Followers(author_id)
+        } else {
+            UserListSource::Following(author_id)
+        };
+        self.user_list_pane
+            .component
+            .do_load_user_list(Some(source));
+        self.user_list_pane.component.invalidate();
+        self.focus = Focus::UserList;
+        self.handle_focus();
+        self.should_render = true;
+    }
+
     fn do_toggle_selected_tweet_starred(&mut self) {
         if let Some(tweet_id) = self.get_selected_tweet_id() {
             if let Some(tweet) = self.store.tweets.lock().unwrap().get(&tweet_id) {
@@ -164,6 +315,102 @@ impl FeedPane {
         }
     }
 
+    /// Mutes the selected tweet's author locally, i.e. only in this client.
+    fn do_toggle_selected_tweet_muted(&mut self) {
+        if let Some(tweet_id) = self.get_selected_tweet_id() {
+            if let Some(tweet) = self.store.tweets.lock().unwrap().get(&tweet_id) {
+                {
+                    let mut user_config = self.store.user_config.lock().unwrap();
+                    let tweet_author = tweet.author("[unknown]");
+
+                    if user_config.is_muted_account(&tweet.author_id) {
+                        user_config.unmute_account(&tweet_author);
+                    } else {
+                        user_config.mute_account(&tweet_author);
+                    }
+                }
+
+                match self.store.save_user_config() {
+                    Ok(()) => self
+                        .should_update_scroll_buffer
+                        .store(true, Ordering::SeqCst),
+                    Err(err) => self.events.send(InternalEvent::LogError(err)).unwrap(),
+                }
+            }
+        }
+    }
+
+    fn do_toggle_selected_author_muted_on_twitter(&self) {
+        let author_id = self.get_selected_tweet_id().and_then(|tweet_id| {
+            let tweets = self.store.tweets.lock().unwrap();
+            tweets.get(&tweet_id).map(|tweet| tweet.author_id.clone())
+        });
+        if let Some(author_id) = author_id {
+            let muted = self.store.toggle_muted_user(&author_id);
+            self.should_update_scroll_buffer
+                .store(true, Ordering::SeqCst);
+
+            let events = self.events.clone();
+            let store = self.store.clone();
+            let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+            let task = tokio::spawn(async move {
+                if let Err(error) = store.sync_muted_user(&author_id, muted).await {
+                    should_update_scroll_buffer.store(true, Ordering::SeqCst);
+                    events.send(InternalEvent::LogError(error)).unwrap();
+                }
+            });
+
+            self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+        }
+    }
+
+    fn toggle_show_muted(&mut self) {
+        self.show_muted = !self.show_muted;
+        self.should_update_scroll_buffer
+            .store(true, Ordering::SeqCst);
+    }
+
+    /// Loads who's muted or blocked on Twitter, so that their tweets are collapsed too.
+    pub fn do_load_muted_users(&self) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            // NB: whatever did load still applies, so no need to leave the feed over the rest
+            let result = store.load_muted_users().await;
+            should_update_scroll_buffer.store(true, Ordering::SeqCst);
+            if let Err(error) = result {
+                let message = format!("Couldn't load mutes and blocks from Twitter: {error}");
+                events.send(InternalEvent::SetMessage(message)).unwrap();
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn do_toggle_selected_tweet_engagement(&self, engagement: Engagement) {
+        if let Some(tweet_id) = self.get_selected_tweet_id() {
+            let engaged = self.store.toggle_engagement(engagement, &tweet_id);
+            self.should_update_scroll_buffer
+                .store(true, Ordering::SeqCst);
+
+            let events = self.events.clone();
+            let store = self.store.clone();
+            let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+            let task = tokio::spawn(async move {
+                if let Err(error) = store.sync_engagement(engagement, &tweet_id, engaged).await {
+                    should_update_scroll_buffer.store(true, Ordering::SeqCst);
+                    events.send(InternalEvent::LogError(error)).unwrap();
+                }
+            });
+
+            self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+        }
+    }
+
     pub fn do_search(&self) {
         let search_term = self.search_bar.component.get_text();
 
@@ -183,11 +430,7 @@ impl FeedPane {
             let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
 
             let task = tokio::spawn(async move {
-                match store
-                    .twitter_client
-                    .user_by_username(&twitter_username)
-                    .await
-                {
+                match store.user_by_username(&twitter_username).await {
                     Ok(user) => match store.load_user_tweets(&user.id, true).await {
                         Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
                         Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
@@ -200,31 +443,25 @@ impl FeedPane {
         } else if search_term.is_empty() {
             self.do_load_page_of_tweets(true);
         } else {
-            self.events
-                .send(InternalEvent::LogError(anyhow!(
-                    "Invalid search term: {}",
-                    search_term
-                )))
-                .unwrap();
+            let (query, window) = match parse_search_window(&search_term) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    self.events.send(InternalEvent::LogError(error)).unwrap();
+                    return;
+                }
+            };
+            let source = FeedSource::Search { query, window };
+            self.do_load_feed(source);
         }
     }
 
     pub fn do_search_starred_accounts(&self, restart: bool) {
-        let user_config = self.store.user_config.lock().unwrap();
-        let query = user_config
-            .starred_accounts
-            .keys()
-            .map(|user_id| format!("from:{}", user_id))
-            .collect::<Vec<String>>()
-            .join(" OR ");
-        drop(user_config);
-
         let events = self.events.clone();
         let store = self.store.clone();
         let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
 
         let task = tokio::spawn(async move {
-            match store.load_search_tweets(&query, restart).await {
+            match store.load_starred_accounts_tweets(restart).await {
                 Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
                 Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
             }
@@ -233,6 +470,73 @@ impl FeedPane {
         self.events.send(InternalEvent::RegisterTask(task)).unwrap();
     }
 
+    fn start_compose(&mut self, compose: Compose) {
+        let prompt = match &compose {
+            Compose::Tweet => "tweet> ",
+            Compose::Reply(_) => "reply> ",
+            Compose::Quote(_) => "quote> ",
+            Compose::MuteKeyword => "mute keyword (again to unmute)> ",
+        };
+        self.compose_bar.component.set_prompt(prompt);
+        self.compose = compose;
+        self.focus = Focus::ComposeBar;
+        self.handle_focus();
+        self.should_render = true;
+    }
+
+    fn do_toggle_muted_keyword(&self, keyword: &str) {
+        let muted = self
+            .store
+            .user_config
+            .lock()
+            .unwrap()
+            .toggle_muted_keyword(keyword);
+        match self.store.save_user_config() {
+            Ok(()) => {
+                self.should_update_scroll_buffer
+                    .store(true, Ordering::SeqCst);
+                let message = if muted {
+                    format!("Muted keyword: {}", keyword.trim())
+                } else {
+                    format!("Unmuted keyword: {}", keyword.trim())
+                };
+                self.events
+                    .send(InternalEvent::SetMessage(message))
+                    .unwrap();
+            }
+            Err(err) => self.events.send(InternalEvent::LogError(err)).unwrap(),
+        }
+    }
+
+    fn do_create_tweet(&self, text: String) {
+        let mut new_tweet = api::NewTweet {
+            text,
+            ..api::NewTweet::default()
+        };
+        match &self.compose {
+            Compose::Tweet | Compose::MuteKeyword => (),
+            Compose::Reply(tweet_id) => {
+                new_tweet.reply = Some(api::NewTweetReply {
+                    in_reply_to_tweet_id: tweet_id.clone(),
+                })
+            }
+            Compose::Quote(tweet_id) => new_tweet.quote_tweet_id = Some(tweet_id.clone()),
+        }
+
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            match store.create_tweet(&new_tweet).await {
+                Ok(_) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
     pub fn log_selected_tweet(&self) {
         self.events
             .send(InternalEvent::LogTweet(self.tweet_selected_id.clone()))
@@ -246,6 +550,9 @@ impl Render for FeedPane {
             || self.scroll_buffer.should_render()
             || self.tweet_pane.component.should_render()
             || self.search_bar.component.should_render()
+            || self.compose_bar.component.should_render()
+            || self.list_picker.component.should_render()
+            || self.user_list_pane.component.should_render()
             || self.should_render
     }
 
@@ -253,6 +560,9 @@ impl Render for FeedPane {
         self.scroll_buffer.invalidate();
         self.tweet_pane.component.invalidate();
         self.search_bar.component.invalidate();
+        self.compose_bar.component.invalidate();
+        self.list_picker.component.invalidate();
+        self.user_list_pane.component.invalidate();
         self.should_render = true;
     }
 
@@ -269,14 +579,32 @@ impl Render for FeedPane {
             self.update_scroll_buffer();
         }
 
-        if self.focus == Focus::SearchBar {
+        let input_bar = match self.focus {
+            Focus::SearchBar => Some(&mut self.search_bar),
+            Focus::ComposeBar => Some(&mut self.compose_bar),
+            _ => None,
+        };
+
+        if self.focus == Focus::ListPicker {
+            self.list_picker.bounding_box = BoundingBox {
+                width: half_width as u16,
+                ..bounding_box
+            };
+            self.list_picker.render_if_necessary(stdout)?;
+        } else if self.focus == Focus::UserList {
+            self.user_list_pane.bounding_box = BoundingBox {
+                width: half_width as u16,
+                ..bounding_box
+            };
+            self.user_list_pane.render_if_necessary(stdout)?;
+        } else if let Some(input_bar) = input_bar {
             // CR: this bounding_box concept is superfluous
-            self.search_bar.bounding_box = BoundingBox {
+            input_bar.bounding_box = BoundingBox {
                 width: half_width as u16,
                 height: 1,
                 ..bounding_box
             };
-            self.search_bar.render_if_necessary(stdout)?;
+            input_bar.render_if_necessary(stdout)?;
 
             // CR: need a generic [clear] method
             let str_clear = " ".repeat(half_width);
@@ -318,6 +646,9 @@ impl Render for FeedPane {
             Focus::FeedPane => self.scroll_buffer.get_cursor(),
             Focus::TweetPaneStack => self.tweet_pane.get_cursor(),
             Focus::SearchBar => self.search_bar.get_cursor(),
+            Focus::ComposeBar => self.compose_bar.get_cursor(),
+            Focus::ListPicker => self.list_picker.get_cursor(),
+            Focus::UserList => self.user_list_pane.get_cursor(),
         }
     }
 }
@@ -328,6 +659,9 @@ impl Input for FeedPane {
             Focus::FeedPane => self.scroll_buffer.handle_focus(),
             Focus::TweetPaneStack => self.tweet_pane.component.handle_focus(),
             Focus::SearchBar => self.search_bar.component.handle_focus(),
+            Focus::ComposeBar => self.compose_bar.component.handle_focus(),
+            Focus::ListPicker => self.list_picker.component.handle_focus(),
+            Focus::UserList => self.user_list_pane.component.handle_focus(),
         }
     }
 
@@ -338,6 +672,9 @@ impl Input for FeedPane {
                     Focus::FeedPane => Focus::TweetPaneStack,
                     Focus::TweetPaneStack => Focus::FeedPane,
                     Focus::SearchBar => Focus::SearchBar,
+                    Focus::ComposeBar => Focus::ComposeBar,
+                    Focus::ListPicker => Focus::ListPicker,
+                    Focus::UserList => Focus::UserList,
                 };
                 self.focus = next_focus;
                 self.handle_focus();
@@ -349,6 +686,36 @@ impl Input for FeedPane {
                     KeyCode::Char('r') => self.do_load_page_of_tweets(true),
                     KeyCode::Char('S') => self.do_toggle_selected_tweet_starred(),
                     KeyCode::Char('s') => self.do_search_starred_accounts(true),
+                    KeyCode::Char('l') => {
+                        self.do_toggle_selected_tweet_engagement(Engagement::Like)
+                    }
+                    KeyCode::Char('t') => {
+                        self.do_toggle_selected_tweet_engagement(Engagement::Retweet)
+                    }
+                    KeyCode::Char('b') => {
+                        self.do_toggle_selected_tweet_engagement(Engagement::Bookmark)
+                    }
+                    KeyCode::Char('B') => self.do_load_feed(FeedSource::Bookmarks),
+                    KeyCode::Char('M') => self.do_load_feed(FeedSource::Mentions),
+                    KeyCode::Char('L') => self.start_list_picker(),
+                    KeyCode::Char('w') => self.start_user_list(true),
+                    KeyCode::Char('W') => self.start_user_list(false),
+                    KeyCode::Char('F') => self.do_follow_stream(),
+                    KeyCode::Char('x') => self.do_toggle_selected_tweet_muted(),
+                    KeyCode::Char('X') => self.toggle_show_muted(),
+                    KeyCode::Char('k') => self.start_compose(Compose::MuteKeyword),
+                    KeyCode::Char('m') => self.do_toggle_selected_author_muted_on_twitter(),
+                    KeyCode::Char('c') => self.start_compose(Compose::Tweet),
+                    KeyCode::Char('R') => {
+                        if let Some(tweet_id) = self.get_selected_tweet_id() {
+                            self.start_compose(Compose::Reply(tweet_id));
+                        }
+                    }
+                    KeyCode::Char('Q') => {
+                        if let Some(tweet_id) = self.get_selected_tweet_id() {
+                            self.start_compose(Compose::Quote(tweet_id));
+                        }
+                    }
                     KeyCode::Char('/') => {
                         self.focus = Focus::SearchBar;
                         self.handle_focus();
@@ -384,7 +751,18 @@ impl Input for FeedPane {
                         return handled;
                     }
                 },
-                Focus::TweetPaneStack => return self.tweet_pane.component.handle_key_event(event),
+                Focus::TweetPaneStack => {
+                    let tweet_id = self.tweet_pane.component.get_focused_tweet_id();
+                    match (event.code, tweet_id) {
+                        (KeyCode::Char('R'), Some(tweet_id)) => {
+                            self.start_compose(Compose::Reply(tweet_id))
+                        }
+                        (KeyCode::Char('Q'), Some(tweet_id)) => {
+                            self.start_compose(Compose::Quote(tweet_id))
+                        }
+                        _ => return self.tweet_pane.component.handle_key_event(event),
+                    }
+                }
                 Focus::SearchBar => match event.code {
                     KeyCode::Esc => {
                         self.focus = Focus::FeedPane;
@@ -398,16 +776,113 @@ impl Input for FeedPane {
                     }
                     _ => return self.search_bar.component.handle_key_event(event),
                 },
+                Focus::ComposeBar => match event.code {
+                    KeyCode::Esc => {
+                        self.compose_bar.component.clear();
+                        self.focus = Focus::FeedPane;
+                        self.handle_focus();
+                    }
+                    KeyCode::Enter => {
+                        let text = self.compose_bar.component.get_text();
+                        if !text.trim().is_empty() {
+                            match self.compose {
+                                Compose::MuteKeyword => self.do_toggle_muted_keyword(&text),
+                                _ => self.do_create_tweet(text),
+                            }
+                        }
+                        self.compose_bar.component.clear();
+                        self.focus = Focus::FeedPane;
+                        self.handle_focus();
+                    }
+                    _ => return self.compose_bar.component.handle_key_event(event),
+                },
+                Focus::ListPicker => match event.code {
+                    KeyCode::Esc => self.return_to_feed_pane(),
+                    KeyCode::Enter => {
+                        if let Some(list_id) = self.list_picker.component.get_selected_list_id() {
+                            self.do_load_feed(FeedSource::List(list_id));
+                        }
+                        self.return_to_feed_pane();
+                    }
+                    _ => return self.list_picker.component.handle_key_event(event),
+                },
+                Focus::UserList => match event.code {
+                    KeyCode::Esc => self.return_to_feed_pane(),
+                    KeyCode::Enter => {
+                        if let Some(user) = self.user_list_pane.component.get_selected_user() {
+                            self.do_load_feed(FeedSource::User(user.id));
+                        }
+                        self.return_to_feed_pane();
+                    }
+                    _ => return self.user_list_pane.component.handle_key_event(event),
+                },
             },
         };
         true
     }
 }
 
+/// Lifts `since:`/`until:` (a date, or a tweet id) out of [search_term] into a window, since
+/// recent search doesn't have those operators.  Dates are days in UTC, and `until:` is exclusive.
+fn parse_search_window(search_term: &str) -> Result<(String, api::SearchWindow)> {
+    let mut window = api::SearchWindow::default();
+    let mut terms = Vec::new();
+
+    for term in search_term.split_whitespace() {
+        let (operator, value) = match term.split_once(':') {
+            Some((operator @ ("since" | "until"), value)) => (operator, value),
+            _ => {
+                terms.push(term);
+                continue;
+            }
+        };
+        if value.chars().all(|c| c.is_ascii_digit()) && value.len() > 8 {
+            match operator {
+                "since" => window.since_id = Some(value.to_string()),
+                _ => window.until_id = Some(value.to_string()),
+            }
+        } else {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| anyhow!("Invalid date or tweet id: {}", term))?;
+            let time = Some(DateTime::<Utc>::from_utc(
+                date.and_hms_opt(0, 0, 0).unwrap(),
+                Utc,
+            ));
+            match operator {
+                "since" => window.start_time = time,
+                _ => window.end_time = time,
+            }
+        }
+    }
+
+    if terms.is_empty() {
+        return Err(anyhow!("Invalid search term: {}", search_term));
+    }
+    Ok((terms.join(" "), window))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_search_window() {
+        let (query, window) =
+            parse_search_window("#rust since:2023-02-01 until:1600000000000000002").unwrap();
+        assert_eq!(query, "#rust");
+        assert_eq!(
+            window,
+            api::SearchWindow {
+                start_time: Some("2023-02-01T00:00:00Z".parse().unwrap()),
+                until_id: Some("1600000000000000002".to_string()),
+                ..api::SearchWindow::default()
+            }
+        );
+
+        assert!(parse_search_window("#rust since:yesterday").is_err());
+        assert!(parse_search_window("since:2023-02-01").is_err());
+    }
+
     #[test]
     fn test_regex() {
         let re_newlines = Regex::new(r"[\r\n]+").unwrap();
@@ -0,0 +1,121 @@
+use crate::store::Store;
+use crate::ui::InternalEvent;
+use crate::ui_framework::bounding_box::BoundingBox;
+use crate::ui_framework::scroll_buffer::{ScrollBuffer, TextSegment};
+use crate::ui_framework::{Input, Render};
+use anyhow::Result;
+use crossterm::event::KeyEvent;
+use crossterm::style::{Color, Colors};
+use std::io::Stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Shows [Store::lists] so that one can be picked as the feed; the choice itself is acted on by
+/// the owning pane.
+pub struct ListPicker {
+    events: UnboundedSender<InternalEvent>,
+    store: Arc<Store>,
+    scroll_buffer: ScrollBuffer,
+    should_update_scroll_buffer: Arc<AtomicBool>,
+}
+
+impl ListPicker {
+    pub fn new(events: &UnboundedSender<InternalEvent>, store: &Arc<Store>) -> Self {
+        Self {
+            events: events.clone(),
+            store: store.clone(),
+            scroll_buffer: ScrollBuffer::new(),
+            should_update_scroll_buffer: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn get_selected_list_id(&self) -> Option<String> {
+        let line_no = self.scroll_buffer.get_cursor_line();
+        let lists = self.store.lists.lock().unwrap();
+        lists.get(line_no).map(|list| list.id.clone())
+    }
+
+    pub fn do_load_lists(&self) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            match store.load_lists().await {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn update_scroll_buffer(&mut self) {
+        self.scroll_buffer.clear();
+
+        let lists = self.store.lists.lock().unwrap();
+        if lists.is_empty() {
+            self.scroll_buffer.push(vec![TextSegment::color(
+                "No lists (yet)",
+                Colors::new(Color::DarkGrey, Color::Reset),
+            )]);
+        }
+
+        for list in lists.iter() {
+            let mut segments = vec![TextSegment::color(
+                &format!("{} ", list.name),
+                Colors::new(Color::DarkCyan, Color::Reset),
+            )];
+            if list.private == Some(true) {
+                segments.push(TextSegment::color(
+                    "[private] ",
+                    Colors::new(Color::DarkGrey, Color::Reset),
+                ));
+            }
+            if let Some(description) = &list.description {
+                segments.push(TextSegment::plain(description));
+            }
+            self.scroll_buffer.push(segments);
+        }
+
+        let y = self.scroll_buffer.get_cursor_line();
+        self.scroll_buffer.move_cursor_to(0, y);
+        self.should_update_scroll_buffer
+            .store(false, Ordering::SeqCst);
+    }
+}
+
+impl Render for ListPicker {
+    fn should_render(&self) -> bool {
+        self.should_update_scroll_buffer.load(Ordering::SeqCst)
+            || self.scroll_buffer.should_render()
+    }
+
+    fn invalidate(&mut self) {
+        self.scroll_buffer.invalidate();
+    }
+
+    fn render(&mut self, stdout: &mut Stdout, bounding_box: BoundingBox) -> Result<()> {
+        if self.should_update_scroll_buffer.load(Ordering::SeqCst) {
+            self.update_scroll_buffer();
+        }
+
+        // NB: drawn over the feed
+        self.scroll_buffer.render_opaque(stdout, bounding_box)
+    }
+
+    fn get_cursor(&self) -> (u16, u16) {
+        self.scroll_buffer.get_cursor()
+    }
+}
+
+impl Input for ListPicker {
+    fn handle_focus(&mut self) {
+        self.scroll_buffer.handle_focus()
+    }
+
+    fn handle_key_event(&mut self, event: &KeyEvent) -> bool {
+        self.scroll_buffer.handle_key_event(event)
+    }
+}
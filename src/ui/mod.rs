@@ -1,12 +1,17 @@
 mod bottom_bar;
+mod dm_pane;
 mod feed_pane;
+mod list_picker;
+mod rich_text;
 mod search_bar;
 mod tweet_pane;
 mod tweet_pane_stack;
+mod user_list_pane;
 
 use crate::store::Store;
-use crate::twitter_client::{api, TwitterClient};
+use crate::twitter_client::{api, TwitterApiError, TwitterClient};
 use crate::ui::bottom_bar::BottomBar;
+use crate::ui::dm_pane::DmPane;
 use crate::ui::feed_pane::FeedPane;
 use crate::ui::tweet_pane::TweetPane;
 use crate::ui_framework::bounding_box::BoundingBox;
@@ -36,6 +41,13 @@ pub enum Mode {
     Interactive,
 }
 
+/// Which pane takes up the screen above the bottom bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ActivePane {
+    Feed,
+    Dms,
+}
+
 /// NB: not totally comfortable with this event bus architecture; the loose coupling is convenient
 /// but it introduces non-deterministic delay, and feels overly general (over time I guess there
 /// will end up being too many enum variants.
@@ -47,6 +59,8 @@ pub enum InternalEvent {
     RegisterTask(tokio::task::JoinHandle<()>),
     LogTweet(String),
     LogError(Error),
+    // NB: for what's worth knowing but not worth leaving the feed over
+    SetMessage(String),
 }
 
 pub struct UI {
@@ -56,6 +70,8 @@ pub struct UI {
     tasks: FuturesUnordered<tokio::task::JoinHandle<()>>,
     store: Arc<Store>,
     feed_pane: Component<FeedPane>,
+    dm_pane: Component<DmPane>,
+    active_pane: ActivePane,
     bottom_bar: Component<BottomBar>,
 }
 
@@ -71,6 +87,7 @@ impl UI {
         let store = Arc::new(Store::new(twitter_client, twitter_user, user_config));
 
         let feed_pane = FeedPane::new(&events_tx, &store);
+        let dm_pane = DmPane::new(&events_tx, &store);
         let bottom_bar = BottomBar::new(&store);
 
         let mut this = Self {
@@ -80,6 +97,8 @@ impl UI {
             tasks: FuturesUnordered::new(),
             store,
             feed_pane: Component::new(feed_pane),
+            dm_pane: Component::new(dm_pane),
+            active_pane: ActivePane::Feed,
             bottom_bar: Component::new(bottom_bar),
         };
 
@@ -89,6 +108,7 @@ impl UI {
 
     pub fn initialize(&mut self) {
         self.feed_pane.component.do_load_page_of_tweets(true);
+        self.feed_pane.component.do_load_muted_users();
         self.set_mode(Mode::Interactive).unwrap();
     }
 
@@ -112,20 +132,41 @@ impl UI {
 
     pub fn resize(&mut self, cols: u16, rows: u16) {
         self.feed_pane.bounding_box = BoundingBox::new(0, 0, cols, rows - 2);
+        self.dm_pane.bounding_box = BoundingBox::new(0, 0, cols, rows - 2);
         self.bottom_bar.bounding_box = BoundingBox::new(0, rows - 1, cols, 1);
     }
 
     pub async fn render(&mut self) -> Result<()> {
-        self.feed_pane.render_if_necessary(&mut self.stdout)?;
+        let focus = match self.active_pane {
+            ActivePane::Feed => {
+                self.feed_pane.render_if_necessary(&mut self.stdout)?;
+                self.feed_pane.get_cursor()
+            }
+            ActivePane::Dms => {
+                self.dm_pane.render_if_necessary(&mut self.stdout)?;
+                self.dm_pane.get_cursor()
+            }
+        };
         self.bottom_bar.render_if_necessary(&mut self.stdout)?;
 
-        let focus = self.feed_pane.get_cursor();
         queue!(&self.stdout, cursor::MoveTo(focus.0, focus.1))?;
 
         self.stdout.flush()?;
         Ok(())
     }
 
+    fn toggle_dm_pane(&mut self) {
+        self.active_pane = match self.active_pane {
+            ActivePane::Feed => {
+                self.dm_pane.component.do_load_dm_events();
+                ActivePane::Dms
+            }
+            ActivePane::Dms => ActivePane::Feed,
+        };
+        self.feed_pane.component.invalidate();
+        self.dm_pane.component.invalidate();
+    }
+
     pub fn log_message(&mut self, message: &str) -> Result<()> {
         self.set_mode(Mode::Log)?;
         println!("{message}\r");
@@ -155,8 +196,21 @@ impl UI {
                     .unwrap();
                 subshell.wait().unwrap();
             }
-            InternalEvent::LogError(err) => {
-                self.log_message(err.to_string().as_str()).unwrap();
+            InternalEvent::LogError(err) => match err.downcast_ref::<TwitterApiError>() {
+                // NB: nothing to read in log mode, just wait it out
+                Some(api_error) if api_error.is_rate_limited() => {
+                    self.bottom_bar
+                        .component
+                        .set_message(Some(api_error.to_string()));
+                }
+                Some(api_error) if api_error.is_unauthorized() => {
+                    let message = format!("{api_error}\r\nAccess token rejected, try --login");
+                    self.log_message(&message).unwrap();
+                }
+                _ => self.log_message(err.to_string().as_str()).unwrap(),
+            },
+            InternalEvent::SetMessage(message) => {
+                self.bottom_bar.component.set_message(Some(message));
             }
         }
     }
@@ -164,12 +218,19 @@ impl UI {
     async fn handle_terminal_event(&mut self, event: &Event) {
         match event {
             Event::Key(key_event) => {
-                let handled = self.feed_pane.component.handle_key_event(key_event);
+                let handled = match self.active_pane {
+                    ActivePane::Feed => self.feed_pane.component.handle_key_event(key_event),
+                    ActivePane::Dms => self.dm_pane.component.handle_key_event(key_event),
+                };
                 if !handled {
                     match key_event.code {
+                        KeyCode::Char('D') => self.toggle_dm_pane(),
                         KeyCode::Esc => {
                             self.set_mode(Mode::Interactive).unwrap();
+                            self.bottom_bar.component.set_message(None);
+                            // NB: log mode painted over whichever pane is active
                             self.feed_pane.component.invalidate();
+                            self.dm_pane.component.invalidate();
                             self.bottom_bar.component.invalidate();
                         }
                         KeyCode::Char('q') => {
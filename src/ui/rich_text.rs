@@ -0,0 +1,402 @@
+use crate::twitter_client::api;
+use crate::ui_framework::scroll_buffer::TextSegment;
+use crossterm::style::{Attribute, Attributes, Color, Colors};
+use std::cmp::Reverse;
+use std::iter;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Highlight {
+    Url,
+    Mention,
+    Hashtag,
+    Cashtag,
+    /// A mention of the signed-in user.
+    SelfMention,
+}
+
+impl Highlight {
+    fn text_segment(&self, text: &str) -> TextSegment {
+        let colors = match self {
+            Highlight::Url => Colors::new(Color::Blue, Color::Reset),
+            Highlight::Mention => Colors::new(Color::Cyan, Color::Reset),
+            Highlight::Hashtag => Colors::new(Color::Magenta, Color::Reset),
+            Highlight::Cashtag => Colors::new(Color::Green, Color::Reset),
+            Highlight::SelfMention => Colors::new(Color::Black, Color::Yellow),
+        };
+        let attributes = match self {
+            Highlight::Url => Attributes::from(Attribute::Underlined),
+            _ => Attributes::default(),
+        };
+        TextSegment::new(text, colors, attributes)
+    }
+}
+
+/// A tweet's text as it should be displayed: HTML entities decoded and `t.co` links replaced by
+/// their display URLs, along with which parts of it to highlight.
+///
+/// NB: highlights are placed by the entities' indices, carried through the replacements above,
+/// so that e.g. a `#rust` inside a link isn't highlighted as a hashtag.  They're kept as byte
+/// ranges into [RichText::text], so that the text can be wrapped first and each line highlighted
+/// on its own.
+#[derive(Debug, Clone)]
+pub struct RichText {
+    pub text: String,
+    // NB: sorted and non-overlapping
+    highlights: Vec<(Range<usize>, Highlight)>,
+}
+
+impl RichText {
+    pub fn from_tweet(tweet: &api::Tweet, self_username: &str) -> Self {
+        let (raw_text, entities) = tweet.full_text();
+        let urls = entities
+            .and_then(|entities| entities.urls.as_ref())
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&api::UrlEntity>>();
+
+        let mut text = String::with_capacity(raw_text.len());
+        // NB: where each code point of the text as served, and of its HTML-decoded form, ended up
+        // in [text]; which of the two the indices count isn't consistent, so both are tried
+        let mut raw_offsets = Vec::with_capacity(raw_text.len() + 1);
+        let mut decoded_offsets = Vec::with_capacity(raw_text.len() + 1);
+        let mut rest = raw_text;
+        while !rest.is_empty() {
+            let offset = text.len();
+            let url = urls.iter().find(|url| {
+                (url.start == raw_offsets.len() || url.start == decoded_offsets.len())
+                    && rest.starts_with(&url.url)
+            });
+            let html_entity = HTML_ENTITIES
+                .iter()
+                .find(|(html_entity, _)| rest.starts_with(html_entity));
+
+            let (consumed, decoded_len) = if let Some(url) = url {
+                text.push_str(display_url(url));
+                (url.url.len(), url.url.chars().count())
+            } else if let Some((html_entity, decoded)) = html_entity {
+                text.push(*decoded);
+                (html_entity.len(), 1)
+            } else {
+                let c = rest.chars().next().unwrap();
+                text.push(c);
+                (c.len_utf8(), 1)
+            };
+            raw_offsets.extend(iter::repeat_n(offset, rest[..consumed].chars().count()));
+            decoded_offsets.extend(iter::repeat_n(offset, decoded_len));
+            rest = &rest[consumed..];
+        }
+        raw_offsets.push(text.len());
+        decoded_offsets.push(text.len());
+
+        let mut highlights = Vec::new();
+        let mut highlight_entity = |start: usize, end: usize, literal: &str, highlight| {
+            for offsets in [&raw_offsets, &decoded_offsets] {
+                if let (Some(&start), Some(&end)) = (offsets.get(start), offsets.get(end)) {
+                    if start < end && is_literal(&text[start..end], literal) {
+                        highlights.push((start..end, highlight));
+                        return;
+                    }
+                }
+            }
+        };
+
+        for url in &urls {
+            highlight_entity(url.start, url.end, display_url(url), Highlight::Url);
+        }
+        if let Some(entities) = entities {
+            for mention in entities.mentions.iter().flatten() {
+                let highlight = if mention.username.eq_ignore_ascii_case(self_username) {
+                    Highlight::SelfMention
+                } else {
+                    Highlight::Mention
+                };
+                let literal = format!("@{}", mention.username);
+                highlight_entity(mention.start, mention.end, &literal, highlight);
+            }
+            for hashtag in entities.hashtags.iter().flatten() {
+                let literal = format!("#{}", hashtag.tag);
+                highlight_entity(hashtag.start, hashtag.end, &literal, Highlight::Hashtag);
+            }
+            for cashtag in entities.cashtags.iter().flatten() {
+                let literal = format!("${}", cashtag.tag);
+                highlight_entity(cashtag.start, cashtag.end, &literal, Highlight::Cashtag);
+            }
+        }
+        // NB: without entities (e.g. a tweet composed locally), still catch mentions of us
+        if entities.is_none_or(|entities| entities.mentions.is_none()) {
+            for range in find_mentions(&text, self_username) {
+                highlights.push((range, Highlight::SelfMention));
+            }
+        }
+
+        Self::new(text, highlights)
+    }
+
+    /// For text that comes without entities, e.g. a DM; only mentions of us are highlighted.
+    pub fn from_text(text: &str, self_username: &str) -> Self {
+        let text = decode_html_entities(text);
+        let highlights = find_mentions(&text, self_username)
+            .into_iter()
+            .map(|range| (range, Highlight::SelfMention))
+            .collect();
+        Self::new(text, highlights)
+    }
+
+    fn new(text: String, mut highlights: Vec<(Range<usize>, Highlight)>) -> Self {
+        // NB: where highlights overlap, e.g. a mention found both ways, the first one wins
+        highlights.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+        let mut last_end = 0;
+        highlights.retain(|(range, _)| {
+            let is_kept = range.start >= last_end && !range.is_empty();
+            if is_kept {
+                last_end = range.end;
+            }
+            is_kept
+        });
+
+        Self { text, highlights }
+    }
+
+    /// Wraps [RichText::text] to [width], splitting each line into highlighted segments.
+    pub fn wrap(&self, width: usize) -> Vec<Vec<TextSegment>> {
+        let mut cursor = 0;
+        textwrap::wrap(&self.text, width)
+            .iter()
+            .map(|line| match self.text[cursor..].find(line.as_ref()) {
+                Some(offset) => {
+                    let start = cursor + offset;
+                    cursor = start + line.len();
+                    self.segments(start..cursor)
+                }
+                None => vec![TextSegment::plain(line)],
+            })
+            .collect()
+    }
+
+    /// Fits the text on one line of [width]: newlines shown as `⏎`, and cut short with `…`.
+    pub fn one_line(&self, width: usize) -> Vec<TextSegment> {
+        let flattened = self.without_newlines();
+        let mut lines = flattened.wrap(width);
+        if lines.len() > 1 {
+            // Rewrap lines to accommodate ellipsis (…), which may knock out a word
+            lines = flattened.wrap(width.saturating_sub(1));
+            lines[0].push(TextSegment::plain("…"));
+        }
+        lines.into_iter().next().unwrap_or_default()
+    }
+
+    fn without_newlines(&self) -> Self {
+        let mut text = String::with_capacity(self.text.len());
+        let mut offsets = Vec::with_capacity(self.text.len() + 1);
+        let mut is_after_newline = false;
+        for c in self.text.chars() {
+            offsets.extend(iter::repeat_n(text.len(), c.len_utf8()));
+            let is_newline = c == '\r' || c == '\n';
+            if !is_newline {
+                text.push(c);
+            } else if !is_after_newline {
+                text.push_str("⏎ ");
+            }
+            is_after_newline = is_newline;
+        }
+        offsets.push(text.len());
+
+        let highlights = self
+            .highlights
+            .iter()
+            .map(|(range, highlight)| (offsets[range.start]..offsets[range.end], *highlight))
+            .collect();
+        Self::new(text, highlights)
+    }
+
+    /// Splits the part of [RichText::text] in [range] into highlighted segments.
+    fn segments(&self, range: Range<usize>) -> Vec<TextSegment> {
+        let mut segments = Vec::new();
+        let mut last_end = range.start;
+
+        for (highlight_range, highlight) in &self.highlights {
+            let start = highlight_range.start.max(range.start);
+            let end = highlight_range.end.min(range.end);
+            if start >= end {
+                continue;
+            }
+            if start > last_end {
+                segments.push(TextSegment::plain(&self.text[last_end..start]));
+            }
+            segments.push(highlight.text_segment(&self.text[start..end]));
+            last_end = end;
+        }
+
+        if last_end < range.end || segments.is_empty() {
+            segments.push(TextSegment::plain(&self.text[last_end..range.end]));
+        }
+        segments
+    }
+}
+
+fn display_url(url: &api::UrlEntity) -> &str {
+    url.display_url
+        .as_ref()
+        .or(url.expanded_url.as_ref())
+        .unwrap_or(&url.url)
+}
+
+/// Whether [found] is what an entity's [literal] says it should be; the leading `@`/`#`/`$` is
+/// left out, since the text may use a full-width one.
+fn is_literal(found: &str, literal: &str) -> bool {
+    fn without_sigil(s: &str) -> &str {
+        s.char_indices().nth(1).map_or("", |(index, _)| &s[index..])
+    }
+    without_sigil(found).eq_ignore_ascii_case(without_sigil(literal))
+}
+
+/// Finds `@username` where it stands on its own, i.e. not e.g. in `foo@username.com`.
+fn find_mentions(text: &str, username: &str) -> Vec<Range<usize>> {
+    let mention = format!("@{username}");
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices('@')
+        .map(|(start, _)| start..start + mention.len())
+        .filter(|range| {
+            text.get(range.clone())
+                .is_some_and(|found| found.eq_ignore_ascii_case(&mention))
+                && !text[..range.start].chars().next_back().is_some_and(is_word)
+                && !text[range.end..].chars().next().is_some_and(is_word)
+        })
+        .collect()
+}
+
+/// NB: the API only escapes these, see
+/// https://developer.twitter.com/en/docs/twitter-api/v1/data-dictionary/object-model/tweet
+const HTML_ENTITIES: [(&str, char); 3] = [("&lt;", '<'), ("&gt;", '>'), ("&amp;", '&')];
+
+fn decode_html_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tweet(text: &str, entities: &str) -> api::Tweet {
+        let tweet = serde_json::json!({
+            "id": "1",
+            "text": text,
+            "created_at": "2023-02-01T12:00:00.000Z",
+            "author_id": "12",
+            "entities": serde_json::from_str::<serde_json::Value>(entities).unwrap(),
+        });
+        serde_json::from_value(tweet).unwrap()
+    }
+
+    fn highlighted(segments: &[TextSegment]) -> Vec<String> {
+        segments
+            .iter()
+            .filter(|segment| segment.colors() != Colors::new(Color::Reset, Color::Reset))
+            .map(|segment| segment.text().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_rich_text() {
+        let tweet = tweet(
+            "@Mock_User @jack $ETH &amp; #rust &lt;3 https://t.co/abc",
+            r##"{
+                "urls": [{"start": 40, "end": 56, "url": "https://t.co/abc", "expanded_url": "https://www.rust-lang.org/", "display_url": "rust-lang.org"}],
+                "mentions": [{"start": 0, "end": 10, "username": "mock_user"}, {"start": 11, "end": 16, "username": "jack"}],
+                "hashtags": [{"start": 28, "end": 33, "tag": "rust"}],
+                "cashtags": [{"start": 17, "end": 21, "tag": "ETH"}]
+            }"##,
+        );
+        let rich_text = RichText::from_tweet(&tweet, "mock_user");
+        assert_eq!(
+            rich_text.text,
+            "@Mock_User @jack $ETH & #rust <3 rust-lang.org"
+        );
+        assert_eq!(
+            highlighted(&rich_text.wrap(80)[0]),
+            vec!["@Mock_User", "@jack", "$ETH", "#rust", "rust-lang.org"]
+        );
+    }
+
+    #[test]
+    fn test_highlights_by_position() {
+        // NB: these indices count the decoded text, i.e. `&amp;` as one
+        let tweet = tweet(
+            "&amp; #rust https://t.co/abc foo@jack.com @jack",
+            r##"{
+                "urls": [{"start": 8, "end": 24, "url": "https://t.co/abc", "display_url": "example.com/#rust"}],
+                "mentions": [{"start": 38, "end": 43, "username": "jack"}],
+                "hashtags": [{"start": 2, "end": 7, "tag": "rust"}]
+            }"##,
+        );
+        let rich_text = RichText::from_tweet(&tweet, "mock_user");
+        assert_eq!(
+            rich_text.text,
+            "& #rust example.com/#rust foo@jack.com @jack"
+        );
+        assert_eq!(
+            highlighted(&rich_text.wrap(80)[0]),
+            vec!["#rust", "example.com/#rust", "@jack"]
+        );
+
+        // NB: a highlight split across lines is highlighted on both
+        let lines = rich_text.wrap(14);
+        assert_eq!(highlighted(&lines[1]), vec!["example.com/"]);
+        assert_eq!(highlighted(&lines[2]), vec!["#rust"]);
+    }
+
+    #[test]
+    fn test_one_line() {
+        let tweet = tweet(
+            "first @jack\n\nsecond line",
+            r#"{"mentions": [{"start": 6, "end": 11, "username": "jack"}]}"#,
+        );
+        let rich_text = RichText::from_tweet(&tweet, "mock_user");
+
+        let line = rich_text.one_line(80);
+        assert_eq!(highlighted(&line), vec!["@jack"]);
+        let text = line
+            .iter()
+            .map(|segment| segment.text())
+            .collect::<String>();
+        assert_eq!(text, "first @jack⏎ second line");
+
+        let line = rich_text.one_line(16);
+        let text = line
+            .iter()
+            .map(|segment| segment.text())
+            .collect::<String>();
+        assert_eq!(text, "first @jack⏎…");
+    }
+
+    #[test]
+    fn test_prefers_note_tweet() {
+        let mut tweet = tweet("#rust is… https://t.co/abc", r#"{"hashtags": []}"#);
+        tweet.note_tweet = Some(api::NoteTweet {
+            text: "#rust is great, and this is the rest of it".to_string(),
+            entities: Some(api::Entities {
+                hashtags: Some(vec![api::TagEntity {
+                    start: 0,
+                    end: 5,
+                    tag: "rust".to_string(),
+                }]),
+                ..api::Entities::default()
+            }),
+        });
+
+        let rich_text = RichText::from_tweet(&tweet, "mock_user");
+        assert_eq!(rich_text.text, "#rust is great, and this is the rest of it");
+        assert_eq!(rich_text.wrap(80)[0][0].text(), "#rust");
+    }
+
+    #[test]
+    fn test_self_mention_without_entities() {
+        let rich_text = RichText::from_text("hi @mock_user, not @mock_user_2", "mock_user");
+        let segments = &rich_text.wrap(80)[0];
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[1].text(), "@mock_user");
+    }
+}
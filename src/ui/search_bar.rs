@@ -8,6 +8,7 @@ use std::io::{Stdout, Write};
 
 #[derive(Debug)]
 pub struct SearchBar {
+    pub prompt: String,
     pub text_input: String,
     pub caret_position: usize,
     pub should_render: bool,
@@ -15,7 +16,12 @@ pub struct SearchBar {
 
 impl SearchBar {
     pub fn new() -> Self {
+        Self::with_prompt("/ ")
+    }
+
+    pub fn with_prompt(prompt: &str) -> Self {
         Self {
+            prompt: prompt.to_string(),
             text_input: "".to_string(),
             caret_position: 0,
             should_render: true,
@@ -27,6 +33,15 @@ impl SearchBar {
         self.text_input.clone()
     }
 
+    pub fn set_prompt(&mut self, prompt: &str) {
+        self.prompt = prompt.to_string();
+        self.should_render = true;
+    }
+
+    fn prompt_width(&self) -> usize {
+        self.prompt.chars().count()
+    }
+
     pub fn clear(&mut self) {
         self.text_input = "".to_string();
         self.caret_position = 0;
@@ -75,12 +90,13 @@ impl Render for SearchBar {
         let BoundingBox { left, top, .. } = bounding_box;
 
         queue!(stdout, cursor::MoveTo(left, top))?;
-        queue!(stdout, style::Print("/ "))?;
+        queue!(stdout, style::Print(&self.prompt))?;
 
         // CR-soon: search bar horizontal scrolling
-        let str_clear = " ".repeat(bounding_box.width.saturating_sub(2) as usize);
+        let prompt_width = self.prompt_width();
+        let str_clear = " ".repeat((bounding_box.width as usize).saturating_sub(prompt_width));
         queue!(stdout, style::Print(str_clear))?;
-        queue!(stdout, cursor::MoveTo(left + 2, top))?;
+        queue!(stdout, cursor::MoveTo(left + prompt_width as u16, top))?;
         queue!(stdout, style::Print(&self.text_input))?;
 
         stdout.flush()?;
@@ -88,7 +104,7 @@ impl Render for SearchBar {
     }
 
     fn get_cursor(&self) -> (u16, u16) {
-        (self.caret_position as u16 + 2, 0)
+        ((self.caret_position + self.prompt_width()) as u16, 0)
     }
 }
 
@@ -1,5 +1,6 @@
 use crate::store::Store;
 use crate::twitter_client::api;
+use crate::ui::rich_text::RichText;
 use crate::ui::InternalEvent;
 use crate::ui_framework::bounding_box::BoundingBox;
 use crate::ui_framework::scroll_buffer::{ScrollBuffer, TextSegment};
@@ -9,7 +10,6 @@ use crossterm::cursor;
 use crossterm::event::{KeyCode, KeyEvent};
 use crossterm::queue;
 use crossterm::style::{self, Color, Colors};
-use regex::Regex;
 use std::collections::HashMap;
 use std::io::{Stdout, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -63,6 +63,7 @@ pub struct TweetPane {
     display_width: usize,
     focus: Focus,
     focus_map: HashMap<Focus, (usize, usize)>,
+    histogram_granularity: api::Granularity,
 }
 
 impl TweetPane {
@@ -80,16 +81,141 @@ impl TweetPane {
             display_width: 0,
             focus: Focus::Tweet,
             focus_map: HashMap::new(),
+            histogram_granularity: api::Granularity::default(),
         }
     }
 
     pub fn set_tweet_id(&mut self, tweet_id: &String) {
-        let mut tweet_details = self.tweet_details.lock().unwrap();
-        tweet_details.tweet_id = tweet_id.clone();
+        let mut tweet_details = TweetDetails::new(tweet_id);
+        if let Some(tweet) = self.store.tweets.lock().unwrap().get(tweet_id) {
+            let mut in_reply_to_ids = Vec::new();
+            for reference in tweet.referenced_tweets.iter().flatten() {
+                match reference.r#type.as_str() {
+                    "replied_to" => in_reply_to_ids.push(reference.id.clone()),
+                    "quoted" => {
+                        tweet_details.quote_id = Some((QuoteType::QuoteTweet, reference.id.clone()))
+                    }
+                    "retweeted" => {
+                        tweet_details.quote_id = Some((QuoteType::Retweet, reference.id.clone()))
+                    }
+                    _ => (),
+                }
+            }
+            tweet_details.in_reply_to_ids = Some(in_reply_to_ids);
+        }
+        *self.tweet_details.lock().unwrap() = tweet_details;
+
+        self.do_load_referenced_tweets(tweet_id);
         self.should_update_scroll_buffer
             .store(true, Ordering::Relaxed);
     }
 
+    fn do_load_referenced_tweets(&self, tweet_id: &str) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+        let tweet_ids = vec![tweet_id.to_string()];
+
+        let task = tokio::spawn(async move {
+            match store.load_referenced_tweets(&tweet_ids).await {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn get_author_id(&self) -> Option<String> {
+        let tweet_id = self.tweet_details.lock().unwrap().tweet_id.clone();
+        self.store
+            .tweets
+            .lock()
+            .unwrap()
+            .get(&tweet_id)
+            .map(|tweet| tweet.author_id.clone())
+    }
+
+    /// NB: only once the pane is focused, rather than for every tweet passed over in the feed.
+    fn do_load_tweet_counts(&self, author_id: &str) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+        let author_id = author_id.to_string();
+        let granularity = self.histogram_granularity;
+
+        let task = tokio::spawn(async move {
+            match store.load_tweet_counts(&author_id, granularity).await {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => {
+                    let message = format!("Couldn't load tweet counts: {error}");
+                    events.send(InternalEvent::SetMessage(message)).unwrap();
+                }
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    /// Switches the author's posting histogram between hourly and daily buckets.
+    fn toggle_histogram_granularity(&mut self) {
+        self.histogram_granularity = match self.histogram_granularity {
+            api::Granularity::Hour => api::Granularity::Day,
+            _ => api::Granularity::Hour,
+        };
+
+        if let Some(author_id) = self.get_author_id() {
+            self.do_load_tweet_counts(&author_id);
+        }
+        self.should_update_scroll_buffer
+            .store(true, Ordering::SeqCst);
+    }
+
+    /// Loads the thread above and the replies below the tweet, unless already done.
+    fn do_resolve_tweet(&self) {
+        let tweet_id = {
+            let tweet_details = self.tweet_details.lock().unwrap();
+            if tweet_details.reply_ids.is_some() {
+                return;
+            }
+            tweet_details.tweet_id.clone()
+        };
+
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let tweet_details = self.tweet_details.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            let result =
+                tokio::try_join!(store.load_thread(&tweet_id), store.load_replies(&tweet_id));
+            match result {
+                Ok((in_reply_to_ids, reply_ids)) => {
+                    let mut tweet_details = tweet_details.lock().unwrap();
+                    // NB: a different tweet may have been selected in the meantime
+                    if tweet_details.tweet_id == tweet_id {
+                        tweet_details.in_reply_to_ids = Some(in_reply_to_ids);
+                        tweet_details.reply_ids = Some(reply_ids);
+                        should_update_scroll_buffer.store(true, Ordering::SeqCst);
+                    }
+                }
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    pub fn get_focused_tweet_id(&self) -> Option<String> {
+        let tweet_details = self.tweet_details.lock().unwrap();
+        match &self.focus {
+            Focus::InReplyTo(i) => tweet_details.in_reply_to_ids.as_ref()?.get(*i).cloned(),
+            Focus::Tweet => Some(tweet_details.tweet_id.clone()),
+            Focus::Reply(i) => tweet_details.reply_ids.as_ref()?.get(*i).cloned(),
+            Focus::Quote => tweet_details.quote_id.as_ref().map(|(_, id)| id.clone()),
+        }
+    }
+
     fn set_focus(&mut self, focus: &Focus) {
         let desired = self.focus_map.get(&focus).map(|cur| (focus, cur));
         let default = self
@@ -147,7 +273,9 @@ impl TweetPane {
     fn update_scroll_buffer_and_focus_map(&mut self) {
         {
             let tweets = self.store.tweets.lock().unwrap();
+            let tweet_counts = self.store.tweet_counts.lock().unwrap();
             let tweet_details = self.tweet_details.lock().unwrap();
+            let self_username = &self.store.twitter_user.username;
 
             let TweetDetails {
                 in_reply_to_ids,
@@ -165,8 +293,11 @@ impl TweetPane {
                         .insert(Focus::InReplyTo(i), (0, self.scroll_buffer.height()));
 
                     if let Some(tweet) = tweets.get(in_reply_to_id) {
-                        self.scroll_buffer
-                            .append(&mut draw_tweet(self.display_width, tweet));
+                        self.scroll_buffer.append(&mut draw_tweet(
+                            self.display_width,
+                            tweet,
+                            self_username,
+                        ));
                     } else {
                         self.scroll_buffer
                             .push(draw_tweet_id(self.display_width, in_reply_to_id));
@@ -185,14 +316,48 @@ impl TweetPane {
                 .insert(Focus::Tweet, (0, self.scroll_buffer.height()));
 
             if let Some(tweet) = tweets.get(tweet_id) {
-                self.scroll_buffer
-                    .append(&mut draw_tweet(self.display_width, tweet));
+                self.scroll_buffer.append(&mut draw_tweet(
+                    self.display_width,
+                    tweet,
+                    self_username,
+                ));
+                let key = (tweet.author_id.clone(), self.histogram_granularity);
+                if let Some(tweet_counts) = tweet_counts.get(&key) {
+                    self.scroll_buffer.push_newline();
+                    self.scroll_buffer.push(draw_tweet_counts(
+                        self.display_width,
+                        self.histogram_granularity,
+                        tweet_counts,
+                    ));
+                }
             } else {
                 self.scroll_buffer
                     .push(draw_tweet_id(self.display_width, tweet_id));
             }
             self.scroll_buffer.push_newline();
 
+            if let Some((quote_type, quote_id)) = quote_id {
+                let str_quote = match quote_type {
+                    QuoteType::Retweet => "⟲ retweet of",
+                    QuoteType::QuoteTweet => "↘ quoting",
+                };
+                self.scroll_buffer.push(vec![TextSegment::plain(str_quote)]);
+                self.focus_map
+                    .insert(Focus::Quote, (0, self.scroll_buffer.height()));
+
+                if let Some(tweet) = tweets.get(quote_id) {
+                    self.scroll_buffer.append(&mut draw_tweet(
+                        self.display_width,
+                        tweet,
+                        self_username,
+                    ));
+                } else {
+                    self.scroll_buffer
+                        .push(draw_tweet_id(self.display_width, quote_id));
+                }
+                self.scroll_buffer.push_newline();
+            }
+
             if let Some(reply_ids) = reply_ids {
                 for (i, reply_id) in reply_ids.iter().enumerate() {
                     let str_indent = "    ↪ ";
@@ -206,7 +371,7 @@ impl TweetPane {
                     let mut line = vec![TextSegment::plain(str_indent)];
 
                     if let Some(tweet) = tweets.get(reply_id) {
-                        line.append(&mut draw_tweet_one_line(rem_width, tweet));
+                        line.append(&mut draw_tweet_one_line(rem_width, tweet, self_username));
                     } else {
                         line.append(&mut draw_tweet_id(rem_width, reply_id));
                     }
@@ -218,8 +383,6 @@ impl TweetPane {
                     .push(vec![TextSegment::plain("<reply_ids?>")]);
                 self.scroll_buffer.push_newline();
             }
-
-            // TODO: QT / RT
         }
 
         let current_focus = self.focus.clone();
@@ -233,13 +396,13 @@ fn draw_tweet_id(_width: usize, tweet_id: &str) -> Vec<TextSegment> {
     vec![TextSegment::plain(&format!("<tweet id: {tweet_id}>"))]
 }
 
-fn draw_tweet(width: usize, tweet: &api::Tweet) -> Vec<Vec<TextSegment>> {
+fn draw_tweet(width: usize, tweet: &api::Tweet, self_username: &str) -> Vec<Vec<TextSegment>> {
     let mut buffer = Vec::new();
     let str_unknown = String::from("[unknown]");
     let tweet_time = tweet.created_at.format("%Y-%m-%d %H:%M:%S");
-    let tweet_author_username = tweet.author_username.as_ref().unwrap_or(&str_unknown);
-    let tweet_author_name = tweet.author_name.as_ref().unwrap_or(&str_unknown);
-    let tweet_lines = textwrap::wrap(&tweet.text, width.saturating_sub(1) as usize);
+    let tweet_author_username = tweet.author_username().unwrap_or(&str_unknown);
+    let tweet_author_name = tweet.author_name().unwrap_or(&str_unknown);
+    let rich_text = RichText::from_tweet(tweet, self_username);
 
     // CR-someday: DSL quote macro, if worthwhile
     buffer.push(vec![TextSegment::plain(&format!("{tweet_time}"))]);
@@ -248,17 +411,116 @@ fn draw_tweet(width: usize, tweet: &api::Tweet) -> Vec<Vec<TextSegment>> {
     ))]);
     buffer.push(vec![]);
 
-    for line in tweet_lines {
-        buffer.push(vec![TextSegment::plain(&line)]);
+    buffer.append(&mut rich_text.wrap(width.saturating_sub(1)));
+
+    buffer.append(&mut draw_tweet_attachments(width, tweet));
+    buffer
+}
+
+fn draw_tweet_attachments(width: usize, tweet: &api::Tweet) -> Vec<Vec<TextSegment>> {
+    let mut buffer = Vec::new();
+    let colors_dim = Colors::new(Color::DarkGrey, Color::Reset);
+
+    for media in tweet.media.iter().flatten() {
+        let mut str_media = format!("[{}", media.r#type);
+        if let (Some(width), Some(height)) = (media.width, media.height) {
+            str_media.push_str(&format!(" {width}x{height}"));
+        }
+        if let Some(duration_ms) = media.duration_ms {
+            str_media.push_str(&format!(" {}s", duration_ms / 1000));
+        }
+        str_media.push(']');
+        if let Some(alt_text) = &media.alt_text {
+            str_media.push_str(&format!(" {alt_text}"));
+        }
+        for line in textwrap::wrap(&str_media, width.saturating_sub(1)) {
+            buffer.push(vec![TextSegment::color(&line, colors_dim)]);
+        }
+    }
+
+    if let Some(poll) = &tweet.poll {
+        let total_votes: u32 = poll.options.iter().map(|option| option.votes).sum();
+        let bar_width = 10;
+        buffer.push(vec![]);
+        for option in &poll.options {
+            let share = if total_votes > 0 {
+                option.votes as f64 / total_votes as f64
+            } else {
+                0.0
+            };
+            let bar = "█".repeat((share * bar_width as f64).round() as usize);
+            let bar = format!("{bar:<bar_width$}");
+            buffer.push(vec![
+                TextSegment::color(&bar, Colors::new(Color::DarkCyan, Color::Reset)),
+                TextSegment::plain(&format!(" {:>3.0}% {}", share * 100.0, option.label)),
+            ]);
+        }
+        let voting_status = poll.voting_status.as_deref().unwrap_or("unknown");
+        buffer.push(vec![TextSegment::color(
+            &format!("{total_votes} votes, {voting_status}"),
+            colors_dim,
+        )]);
+    }
+
+    if let Some(place) = &tweet.place {
+        buffer.push(vec![TextSegment::color(
+            &format!("📍 {}", place.full_name),
+            colors_dim,
+        )]);
     }
 
     buffer
 }
 
-fn draw_tweet_one_line(width: usize, tweet: &api::Tweet) -> Vec<TextSegment> {
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draws the most recent of [counts] that fit in [width] as a sparkline, one character per
+/// bucket, scaled to the busiest of them; empty buckets are left blank.
+fn sparkline(width: usize, counts: &[i64]) -> String {
+    let counts = &counts[counts.len().saturating_sub(width)..];
+    let max = counts.iter().copied().max().unwrap_or(0);
+    counts
+        .iter()
+        .map(|&count| {
+            if count <= 0 {
+                ' '
+            } else {
+                let level = (count * SPARKS.len() as i64 + max - 1) / max;
+                SPARKS[level.clamp(1, SPARKS.len() as i64) as usize - 1]
+            }
+        })
+        .collect()
+}
+
+fn draw_tweet_counts(
+    width: usize,
+    granularity: api::Granularity,
+    tweet_counts: &[api::TweetCount],
+) -> Vec<TextSegment> {
+    let colors_dim = Colors::new(Color::DarkGrey, Color::Reset);
+    let counts = tweet_counts
+        .iter()
+        .map(|tweet_count| tweet_count.tweet_count)
+        .collect::<Vec<i64>>();
+    let total: i64 = counts.iter().sum();
+    let str_label = format!("per {} ", granularity.as_str());
+    let str_total = format!(" {total} in 7d");
+    let spark_width = width.saturating_sub(str_label.len() + str_total.len() + 1);
+
+    vec![
+        TextSegment::color(&str_label, colors_dim),
+        TextSegment::color(
+            &sparkline(spark_width, &counts),
+            Colors::new(Color::DarkCyan, Color::Reset),
+        ),
+        TextSegment::color(&str_total, colors_dim),
+    ]
+}
+
+fn draw_tweet_one_line(width: usize, tweet: &api::Tweet, self_username: &str) -> Vec<TextSegment> {
     // CR: factor str_unknown to 'static
     let str_unknown = String::from("[unknown]");
-    let tweet_author = tweet.author_username.as_ref().unwrap_or(&str_unknown);
+    let tweet_author = tweet.author_username().unwrap_or(&str_unknown);
     let tweet_author = format!("@{tweet_author} ");
 
     let mut line = vec![
@@ -266,19 +528,9 @@ fn draw_tweet_one_line(width: usize, tweet: &api::Tweet) -> Vec<TextSegment> {
         TextSegment::color(&tweet_author, Colors::new(Color::DarkCyan, Color::Black)),
     ];
 
-    // TODO: this should be factored, same as feed_pane
-    let re_newlines = Regex::new(r"[\r\n]+").unwrap();
-    let formatted = re_newlines.replace_all(&tweet.text, "⏎ ");
-    let remaining_length = width.saturating_sub(tweet_author.len() + 6) as usize;
-    let lines = textwrap::wrap(&formatted, remaining_length);
-    if lines.len() == 1 {
-        line.push(TextSegment::plain(&lines[0]));
-    } else if lines.len() > 1 {
-        // Rewrap lines to accommodate ellipsis (…), which may knock out a word
-        let remaining_length = remaining_length.saturating_sub(1);
-        let lines = textwrap::wrap(&formatted, remaining_length);
-        line.push(TextSegment::plain(&format!("{}…", &lines[0])));
-    }
+    let rich_text = RichText::from_tweet(tweet, self_username);
+    let remaining_length = width.saturating_sub(tweet_author.len() + 6);
+    line.append(&mut rich_text.one_line(remaining_length));
 
     line
 }
@@ -330,13 +582,18 @@ impl Render for TweetPane {
 
 impl Input for TweetPane {
     fn handle_focus(&mut self) {
+        self.do_resolve_tweet();
+        if let Some(author_id) = self.get_author_id() {
+            self.do_load_tweet_counts(&author_id);
+        }
         self.scroll_buffer.handle_focus()
     }
 
     fn handle_key_event(&mut self, event: &KeyEvent) -> bool {
         match event.code {
-            KeyCode::Up => (),
-            KeyCode::Down => (),
+            KeyCode::Up => self.update_focus(-1),
+            KeyCode::Down => self.update_focus(1),
+            KeyCode::Char('h') => self.toggle_histogram_granularity(),
             _ => return self.scroll_buffer.handle_key_event(event),
         };
         true
@@ -366,6 +623,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(10, &[0, 1, 4, 8, 3]), " ▁▄█▃");
+        // NB: only the most recent buckets fit
+        assert_eq!(sparkline(2, &[8, 0, 2]), " █");
+        assert_eq!(sparkline(3, &[0, 0]), "  ");
+        assert_eq!(sparkline(0, &[1]), "");
+    }
+
     #[test]
     fn test_focus_eq() {
         let l = Focus::InReplyTo(3);
@@ -14,12 +14,6 @@ use std::io::Stdout;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::UnboundedSender;
 
-// TODO:
-// on focus, attempt to "resolve" a tweet pane
-// that is, load all thread tweets until in_reply_to_ids[0].id == tweet.conversation_id
-//     and, load quoted tweet
-//     and, load one page of replies
-
 // TODO: at this point, let's research a bit into tui-rs and see
 // if some of these problems we're acc-ing haven't simply been solved
 
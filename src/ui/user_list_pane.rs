@@ -0,0 +1,239 @@
+use crate::store::{Store, UserListSource};
+use crate::twitter_client::api;
+use crate::ui::InternalEvent;
+use crate::ui_framework::bounding_box::BoundingBox;
+use crate::ui_framework::scroll_buffer::{ScrollBuffer, TextSegment};
+use crate::ui_framework::{Input, Render};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::style::{Color, Colors};
+use std::io::Stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Shows [Store::user_list], an account's followers or following, where they can be starred
+/// and followed; opening one's tweets is left to the owning pane.
+pub struct UserListPane {
+    events: UnboundedSender<InternalEvent>,
+    store: Arc<Store>,
+    scroll_buffer: ScrollBuffer,
+    should_update_scroll_buffer: Arc<AtomicBool>,
+}
+
+impl UserListPane {
+    pub fn new(events: &UnboundedSender<InternalEvent>, store: &Arc<Store>) -> Self {
+        Self {
+            events: events.clone(),
+            store: store.clone(),
+            scroll_buffer: ScrollBuffer::new(),
+            should_update_scroll_buffer: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn get_selected_user(&self) -> Option<api::User> {
+        let line_no = self.scroll_buffer.get_cursor_line();
+        let user_list = self.store.user_list.lock().unwrap();
+        let users = self.store.users.lock().unwrap();
+        user_list
+            .get(line_no)
+            .and_then(|user_id| users.get(user_id))
+            .cloned()
+    }
+
+    pub fn do_load_user_list(&self, source: Option<UserListSource>) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        if source.is_some() && !self.store.has_loaded_followed_users() {
+            self.do_load_followed_users();
+        }
+
+        let task = tokio::spawn(async move {
+            // NB: without a new source, continue with the next page of the current one
+            let result = match source {
+                Some(source) => store.load_user_list(&source, true).await,
+                None => {
+                    let source = store.user_list_source.lock().unwrap().clone();
+                    match source {
+                        Some(source) => store.load_user_list(&source, false).await,
+                        None => Ok(()),
+                    }
+                }
+            };
+            match result {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn do_load_followed_users(&self) {
+        let events = self.events.clone();
+        let store = self.store.clone();
+        let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+        let task = tokio::spawn(async move {
+            match store.load_followed_users().await {
+                Ok(()) => should_update_scroll_buffer.store(true, Ordering::SeqCst),
+                Err(error) => events.send(InternalEvent::LogError(error)).unwrap(),
+            }
+        });
+
+        self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+    }
+
+    fn do_toggle_selected_user_starred(&self) {
+        if let Some(user) = self.get_selected_user() {
+            {
+                let mut user_config = self.store.user_config.lock().unwrap();
+                if user_config.is_starred(&user.id) {
+                    user_config.unstar_account(&user);
+                } else {
+                    user_config.star_account(&user);
+                }
+            }
+
+            match self.store.save_user_config() {
+                Ok(()) => self
+                    .should_update_scroll_buffer
+                    .store(true, Ordering::SeqCst),
+                Err(err) => self.events.send(InternalEvent::LogError(err)).unwrap(),
+            }
+        }
+    }
+
+    fn do_toggle_selected_user_following(&self) {
+        if let Some(user) = self.get_selected_user() {
+            let following = match self.store.toggle_following(&user.id) {
+                Ok(following) => following,
+                Err(error) => {
+                    let message = error.to_string();
+                    self.events
+                        .send(InternalEvent::SetMessage(message))
+                        .unwrap();
+                    return;
+                }
+            };
+            self.should_update_scroll_buffer
+                .store(true, Ordering::SeqCst);
+
+            let events = self.events.clone();
+            let store = self.store.clone();
+            let should_update_scroll_buffer = self.should_update_scroll_buffer.clone();
+
+            let task = tokio::spawn(async move {
+                let result = store.sync_following(&user.id, following).await;
+                should_update_scroll_buffer.store(true, Ordering::SeqCst);
+                if let Err(error) = result {
+                    events.send(InternalEvent::LogError(error)).unwrap();
+                }
+            });
+
+            self.events.send(InternalEvent::RegisterTask(task)).unwrap();
+        }
+    }
+
+    fn update_scroll_buffer(&mut self) {
+        self.scroll_buffer.clear();
+
+        let user_list = self.store.user_list.lock().unwrap();
+        let users = self.store.users.lock().unwrap();
+        let user_config = self.store.user_config.lock().unwrap();
+        if user_list.is_empty() {
+            self.scroll_buffer.push(vec![TextSegment::color(
+                "No accounts (yet)",
+                Colors::new(Color::DarkGrey, Color::Reset),
+            )]);
+        }
+
+        for user in user_list.iter().filter_map(|user_id| users.get(user_id)) {
+            let mut segments = vec![TextSegment::color(
+                &format!("@{} ", user.username),
+                Colors::new(Color::DarkCyan, Color::Reset),
+            )];
+            if user_config.is_starred(&user.id) {
+                segments.push(TextSegment::color(
+                    "★ ",
+                    Colors::new(Color::Yellow, Color::Reset),
+                ));
+            }
+            if self.store.is_following(&user.id) {
+                segments.push(TextSegment::color(
+                    "✓ ",
+                    Colors::new(Color::Green, Color::Reset),
+                ));
+            } else if self.store.is_pending_follow(&user.id) {
+                segments.push(TextSegment::color(
+                    "[requested] ",
+                    Colors::new(Color::DarkGrey, Color::Reset),
+                ));
+            }
+            if user.protected == Some(true) {
+                segments.push(TextSegment::color(
+                    "[protected] ",
+                    Colors::new(Color::DarkGrey, Color::Reset),
+                ));
+            }
+            segments.push(TextSegment::plain(&format!("[{}] ", user.name)));
+            if let Some(public_metrics) = &user.public_metrics {
+                segments.push(TextSegment::color(
+                    &format!("{} followers ", public_metrics.followers_count),
+                    Colors::new(Color::DarkGrey, Color::Reset),
+                ));
+            }
+            if let Some(description) = &user.description {
+                segments.push(TextSegment::plain(&description.replace(['\r', '\n'], " ")));
+            }
+            self.scroll_buffer.push(segments);
+        }
+
+        let y = self.scroll_buffer.get_cursor_line();
+        self.scroll_buffer.move_cursor_to(0, y);
+        self.should_update_scroll_buffer
+            .store(false, Ordering::SeqCst);
+    }
+}
+
+impl Render for UserListPane {
+    fn should_render(&self) -> bool {
+        self.should_update_scroll_buffer.load(Ordering::SeqCst)
+            || self.scroll_buffer.should_render()
+    }
+
+    fn invalidate(&mut self) {
+        self.scroll_buffer.invalidate();
+    }
+
+    fn render(&mut self, stdout: &mut Stdout, bounding_box: BoundingBox) -> Result<()> {
+        if self.should_update_scroll_buffer.load(Ordering::SeqCst) {
+            self.update_scroll_buffer();
+        }
+
+        // NB: drawn over the feed
+        self.scroll_buffer.render_opaque(stdout, bounding_box)
+    }
+
+    fn get_cursor(&self) -> (u16, u16) {
+        self.scroll_buffer.get_cursor()
+    }
+}
+
+impl Input for UserListPane {
+    fn handle_focus(&mut self) {
+        self.scroll_buffer.handle_focus()
+    }
+
+    fn handle_key_event(&mut self, event: &KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Char('n') => self.do_load_user_list(None),
+            KeyCode::Char('S') => self.do_toggle_selected_user_starred(),
+            KeyCode::Char('f') => self.do_toggle_selected_user_following(),
+            _ => return self.scroll_buffer.handle_key_event(event),
+        }
+        true
+    }
+}
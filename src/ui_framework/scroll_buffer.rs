@@ -79,6 +79,30 @@ impl ScrollBuffer {
     pub fn get_cursor_line(&self) -> usize {
         self.cursor_position.1
     }
+
+    /// Like [Render::render], but also blanks the rows below the last line, since [render] only
+    /// paints its own lines; for buffers drawn over another pane.
+    pub fn render_opaque(&mut self, stdout: &mut Stdout, bounding_box: BoundingBox) -> Result<()> {
+        if self.should_render || bounding_box != self.last_bounding_box {
+            let BoundingBox {
+                left,
+                top,
+                width,
+                height,
+            } = bounding_box;
+            let str_clear = " ".repeat(width as usize);
+            let first_unused = self
+                .lines
+                .len()
+                .saturating_sub(self.display_offset)
+                .min(height as usize);
+            for delta in first_unused as u16..height {
+                queue!(stdout, cursor::MoveTo(left, top + delta))?;
+                queue!(stdout, style::Print(&str_clear))?;
+            }
+        }
+        self.render(stdout, bounding_box)
+    }
 }
 
 impl Render for ScrollBuffer {
@@ -191,4 +215,12 @@ impl TextSegment {
             Attributes::default(),
         )
     }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn colors(&self) -> Colors {
+        self.colors
+    }
 }
@@ -5,6 +5,12 @@ use std::collections::HashMap;
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct UserConfig {
     pub starred_accounts: HashMap<String, api::User>,
+    // NB: muted locally, i.e. without telling Twitter; see [Store::is_muted] for both kinds
+    #[serde(default)]
+    pub muted_accounts: HashMap<String, api::User>,
+    // NB: matched case-insensitively anywhere in a tweet's text; added or removed with `k`
+    #[serde(default)]
+    pub muted_keywords: Vec<String>,
 }
 
 impl UserConfig {
@@ -20,4 +26,80 @@ impl UserConfig {
     pub fn unstar_account(&mut self, user: &api::User) {
         self.starred_accounts.remove(&user.id.to_string());
     }
+
+    pub fn is_muted_account(&self, user_id: &str) -> bool {
+        self.muted_accounts.contains_key(user_id)
+    }
+
+    pub fn mute_account(&mut self, user: &api::User) {
+        self.muted_accounts
+            .insert(user.id.to_string(), user.clone());
+    }
+
+    pub fn unmute_account(&mut self, user: &api::User) {
+        self.muted_accounts.remove(&user.id.to_string());
+    }
+
+    /// Adds [keyword] to the muted keywords, or removes it if it's already there (ignoring case);
+    /// returns whether it's now muted.
+    pub fn toggle_muted_keyword(&mut self, keyword: &str) -> bool {
+        let keyword = keyword.trim();
+        let len = self.muted_keywords.len();
+        self.muted_keywords
+            .retain(|muted_keyword| !muted_keyword.eq_ignore_ascii_case(keyword));
+        let muted = self.muted_keywords.len() == len;
+        if muted {
+            self.muted_keywords.push(keyword.to_string());
+        }
+        muted
+    }
+
+    /// Whether [tweet] should be hidden, by its author or by any of the muted keywords.
+    pub fn is_muted(&self, tweet: &api::Tweet) -> bool {
+        if self.is_muted_account(&tweet.author_id) {
+            return true;
+        }
+        let (text, _) = tweet.full_text();
+        let text = text.to_lowercase();
+        self.muted_keywords
+            .iter()
+            .filter(|keyword| !keyword.is_empty())
+            .any(|keyword| text.contains(&keyword.to_lowercase()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_muted() {
+        let tweet: api::Tweet = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "text": "Spoilers for the FINALE ahead",
+            "created_at": "2023-02-01T12:00:00.000Z",
+            "author_id": "12",
+        }))
+        .unwrap();
+        let author = tweet.author("jack");
+
+        let mut user_config = UserConfig::default();
+        assert!(!user_config.is_muted(&tweet));
+
+        user_config.mute_account(&author);
+        assert!(user_config.is_muted(&tweet));
+        user_config.unmute_account(&author);
+        assert!(!user_config.is_muted(&tweet));
+
+        user_config.muted_keywords = vec!["".to_string(), "finale".to_string()];
+        assert!(user_config.is_muted(&tweet));
+        assert!(!user_config.toggle_muted_keyword("Finale"));
+        assert!(!user_config.is_muted(&tweet));
+        assert!(user_config.toggle_muted_keyword(" spoilers "));
+        assert!(user_config.is_muted(&tweet));
+
+        // NB: configs saved before muting existed still load
+        let user_config: UserConfig = serde_json::from_str(r#"{"starred_accounts":{}}"#).unwrap();
+        assert!(user_config.muted_keywords.is_empty());
+    }
 }